@@ -0,0 +1,182 @@
+use super::*;
+
+const ACHIEVEMENTS_PATH: &str = "achievements.json";
+const HEAD_HUNTER_KILLS: usize = 10;
+const NO_HEALING_STAGE: usize = 10;
+const HEAVY_HITTER_STRENGTH: f32 = 300.0;
+const VETERAN_STAGE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AchievementId {
+    FirstBlood,
+    HeadHunter,
+    NoHealing,
+    Defused,
+    HeavyHitter,
+    Veteran,
+}
+
+impl AchievementId {
+    pub const ALL: [AchievementId; 6] = [
+        AchievementId::FirstBlood,
+        AchievementId::HeadHunter,
+        AchievementId::NoHealing,
+        AchievementId::Defused,
+        AchievementId::HeavyHitter,
+        AchievementId::Veteran,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AchievementId::FirstBlood => "FIRST BLOOD",
+            AchievementId::HeadHunter => "HEAD HUNTER",
+            AchievementId::NoHealing => "NO HEALING",
+            AchievementId::Defused => "DEFUSED",
+            AchievementId::HeavyHitter => "HEAVY HITTER",
+            AchievementId::Veteran => "VETERAN",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            AchievementId::FirstBlood => "KILL AN ENEMY",
+            AchievementId::HeadHunter => "KILL 10 ENEMIES WITH THE HEAD IN ONE WAVE",
+            AchievementId::NoHealing => "CLEAR STAGE 10 WITHOUT HEALING",
+            AchievementId::Defused => "KILL A BOMBER BEFORE IT EXPLODES",
+            AchievementId::HeavyHitter => "HIT AN ENEMY WITH THE HEAD REALLY HARD",
+            AchievementId::Veteran => "REACH STAGE 20",
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Achievements {
+    unlocked: Vec<AchievementId>,
+    #[serde(skip)]
+    head_kills: usize,
+    #[serde(skip)]
+    new_unlocks: Vec<AchievementId>,
+}
+
+impl Achievements {
+    pub fn load() -> Self {
+        std::fs::read_to_string(ACHIEVEMENTS_PATH)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(contents) => {
+                if let Err(error) = std::fs::write(ACHIEVEMENTS_PATH, contents) {
+                    log_error!("Failed to save achievements: {}", error);
+                }
+            }
+            Err(error) => log_error!("Failed to serialize achievements: {}", error),
+        }
+    }
+
+    pub fn is_unlocked(&self, achievement: AchievementId) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// Unlocks since the last call, which still need to be saved
+    pub fn take_new_unlocks(&mut self) -> Vec<AchievementId> {
+        std::mem::take(&mut self.new_unlocks)
+    }
+
+    /// Forgets progress counted towards the previous run
+    pub fn start_run(&mut self) {
+        self.head_kills = 0;
+    }
+
+    pub fn check_stats(&mut self, stats: &RunStats) {
+        if stats.wave_times.len() >= NO_HEALING_STAGE && stats.healing_received <= 0.0 {
+            self.unlock(AchievementId::NoHealing);
+        }
+    }
+
+    fn unlock(&mut self, achievement: AchievementId) {
+        if !self.is_unlocked(achievement) {
+            self.unlocked.push(achievement);
+            self.new_unlocks.push(achievement);
+        }
+    }
+}
+
+impl EventHandler for Achievements {
+    fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::NextWave { stage } => {
+                self.head_kills = 0;
+                if stage >= VETERAN_STAGE {
+                    self.unlock(AchievementId::Veteran);
+                }
+            }
+            Event::EntityDied {
+                entity_kind: EntityKind::Enemy(enemy_kind),
+                cause,
+                ..
+            } => {
+                self.unlock(AchievementId::FirstBlood);
+                if cause == DamageSource::PlayerHead {
+                    self.head_kills += 1;
+                    if self.head_kills >= HEAD_HUNTER_KILLS {
+                        self.unlock(AchievementId::HeadHunter);
+                    }
+                }
+                // Bombers that go off never die, so any player kill is one before the explosion
+                if enemy_kind == EnemyKind::Bomber
+                    && matches!(cause, DamageSource::PlayerHead | DamageSource::PlayerBody)
+                {
+                    self.unlock(AchievementId::Defused);
+                }
+            }
+            Event::HeadHit { strength, .. } if strength >= HEAVY_HITTER_STRENGTH => {
+                self.unlock(AchievementId::HeavyHitter);
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bomber_died(cause: DamageSource) -> Event {
+        Event::EntityDied {
+            entity_kind: EntityKind::Enemy(EnemyKind::Bomber),
+            cause,
+            position: Vec2::ZERO,
+        }
+    }
+
+    #[test]
+    fn defused_needs_a_player_kill() {
+        let mut achievements = Achievements::default();
+        achievements.handle_event(&bomber_died(DamageSource::Entity(EntityKind::Explosion)));
+        achievements.handle_event(&bomber_died(DamageSource::Expired));
+        assert!(!achievements.is_unlocked(AchievementId::Defused));
+
+        achievements.handle_event(&bomber_died(DamageSource::PlayerBody));
+        assert!(achievements.is_unlocked(AchievementId::Defused));
+    }
+
+    #[test]
+    fn head_kills_reset_with_the_run() {
+        let mut achievements = Achievements::default();
+        let head_kill = Event::EntityDied {
+            entity_kind: EntityKind::Enemy(EnemyKind::Melee),
+            cause: DamageSource::PlayerHead,
+            position: Vec2::ZERO,
+        };
+        for _ in 1..HEAD_HUNTER_KILLS {
+            achievements.handle_event(&head_kill);
+        }
+        achievements.start_run();
+        achievements.handle_event(&head_kill);
+        assert!(!achievements.is_unlocked(AchievementId::HeadHunter));
+    }
+}
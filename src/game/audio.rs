@@ -0,0 +1,140 @@
+use super::*;
+use std::collections::HashMap;
+
+const MIN_INTENSITY_VOLUME: f32 = 0.2;
+/// How much quieter sounds at the left or right screen edge are than ones in the center
+const EDGE_VOLUME_FALLOFF: f32 = 0.5;
+
+pub struct Mixer {
+    pub listener: Listener,
+    pub volume: f32,
+    time: f32,
+    channels: HashMap<EventSound, Channel>,
+}
+
+struct Channel {
+    sound: Sound,
+    duration: f32,
+    max_voices: usize,
+    repeat_interval: f32,
+    full_intensity: f32,
+    voices: Vec<f32>,
+    last_played: Option<f32>,
+}
+
+impl Channel {
+    fn new(
+        sound: Sound,
+        duration: f32,
+        max_voices: usize,
+        repeat_interval: f32,
+        full_intensity: f32,
+    ) -> Self {
+        Self {
+            sound,
+            duration,
+            max_voices,
+            repeat_interval,
+            full_intensity,
+            voices: Vec::with_capacity(max_voices),
+            last_played: None,
+        }
+    }
+}
+
+pub struct Listener {
+    pub position: Vec2,
+    pub half_width: f32,
+}
+
+impl Mixer {
+    pub fn new(assets: &Assets) -> Self {
+        let mut channels = HashMap::new();
+        channels.insert(
+            EventSound::BodyHit,
+            Channel::new(assets.body_hit, 0.3, 3, 0.05, 150.0),
+        );
+        channels.insert(
+            EventSound::HeadHit,
+            Channel::new(assets.head_hit, 0.12, 4, 0.04, 200.0),
+        );
+        channels.insert(
+            EventSound::Death,
+            Channel::new(assets.death, 1.0, 1, 0.5, 1.0),
+        );
+        channels.insert(
+            EventSound::Bounce,
+            Channel::new(assets.bounce, 0.15, 3, 0.06, 30.0),
+        );
+        channels.insert(
+            EventSound::Explosion,
+            Channel::new(assets.explosion, 0.24, 2, 0.1, 500.0),
+        );
+        Self {
+            listener: Listener {
+                position: Vec2::ZERO,
+                half_width: 1.0,
+            },
+            volume: 1.0,
+            time: 0.0,
+            channels,
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.time += delta_time;
+        let time = self.time;
+        for channel in self.channels.values_mut() {
+            channel.voices.retain(|&end_time| end_time > time);
+        }
+    }
+
+    pub fn play(&mut self, sound: EventSound, position: Vec2, intensity: f32) {
+        let time = self.time;
+        let listener = &self.listener;
+        let channel = match self.channels.get_mut(&sound) {
+            Some(channel) => channel,
+            None => return,
+        };
+        if channel.voices.len() >= channel.max_voices
+            || channel
+                .last_played
+                .is_some_and(|last_played| time - last_played < channel.repeat_interval)
+        {
+            return;
+        }
+
+        // Macroquad can't pan sounds, so off-center sounds only get quieter
+        let edge = ((position.x - listener.position.x) / listener.half_width)
+            .abs()
+            .min(1.0);
+        let intensity = (intensity / channel.full_intensity).clamp(MIN_INTENSITY_VOLUME, 1.0);
+        let volume = self.volume * intensity * (1.0 - edge * EDGE_VOLUME_FALLOFF);
+        if volume <= 0.0 {
+            return;
+        }
+
+        macroquad::audio::play_sound(
+            channel.sound,
+            PlaySoundParams {
+                looped: false,
+                volume,
+            },
+        );
+        channel.voices.push(time + channel.duration);
+        channel.last_played = Some(time);
+    }
+}
+
+impl EventHandler for Mixer {
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Sound {
+            sound,
+            position,
+            intensity,
+        } = *event
+        {
+            self.play(sound, position, intensity);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use super::*;
+use std::collections::VecDeque;
+
+/// Lines in this file are run as console commands when the game starts
+const STARTUP_SCRIPT: &str = "startup.txt";
+const OUTPUT_LINES: usize = 10;
+const FONT_SIZE: f32 = 20.0;
+const LINE_HEIGHT: f32 = 20.0;
+/// Distance from the player of enemies spawned without a position
+const SPAWN_DISTANCE: f32 = 40.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleCommand {
+    Spawn {
+        kind: EnemyKind,
+        count: usize,
+        position: Option<Vec2>,
+    },
+    Stage(usize),
+    God,
+    KillAll,
+    Set {
+        name: String,
+        value: f32,
+    },
+    TimeScale(f32),
+    Heal,
+    Seed(u64),
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["spawn", kind, rest @ ..] => {
+                let kind = EnemyKind::ALL
+                    .iter()
+                    .copied()
+                    .find(|enemy_kind| enemy_kind.to_string() == *kind)
+                    .ok_or_else(|| format!("unknown enemy: {}", kind))?;
+                let (count, rest) = match rest {
+                    [count, rest @ ..] if *count != "at" => (parse_value(count)?, rest),
+                    _ => (1, rest),
+                };
+                let position = match rest {
+                    [] => None,
+                    ["at", x, y] => Some(vec2(parse_finite(x)?, parse_finite(y)?)),
+                    _ => return Err("usage: spawn <enemy> [count] [at <x> <y>]".to_owned()),
+                };
+                Ok(Self::Spawn {
+                    kind,
+                    count,
+                    position,
+                })
+            }
+            ["stage", stage] => Ok(Self::Stage(parse_value(stage)?)),
+            ["god"] => Ok(Self::God),
+            ["kill", "all"] => Ok(Self::KillAll),
+            ["set", name, value] => Ok(Self::Set {
+                name: (*name).to_owned(),
+                value: parse_finite(value)?,
+            }),
+            ["timescale", scale] => Ok(Self::TimeScale(parse_finite(scale)?)),
+            ["heal"] => Ok(Self::Heal),
+            ["seed", seed] => Ok(Self::Seed(parse_value(seed)?)),
+            [] => Err("empty command".to_owned()),
+            _ => Err(format!("unknown command: {}", line.trim())),
+        }
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("invalid number: {}", word))
+}
+
+/// Infinity and NaN parse as floats but break the simulation
+fn parse_finite(word: &str) -> Result<f32, String> {
+    let value: f32 = parse_value(word)?;
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(format!("invalid number: {}", word))
+    }
+}
+
+#[derive(Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    history: Vec<String>,
+    /// Position while browsing the history with the arrow keys
+    history_index: Option<usize>,
+    output: VecDeque<String>,
+}
+
+impl Console {
+    pub fn startup_script() -> Vec<String> {
+        std::fs::read_to_string(STARTUP_SCRIPT)
+            .map(|contents| {
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn print(&mut self, line: String) {
+        if self.output.len() == OUTPUT_LINES {
+            self.output.pop_front();
+        }
+        self.output.push_back(line);
+    }
+
+    /// Handles typing, returns a command line once it is submitted
+    pub fn update(&mut self) -> Option<String> {
+        let mut typed = Vec::new();
+        while let Some(character) = get_char_pressed() {
+            typed.push(character);
+        }
+        if is_key_pressed(KeyCode::GraveAccent) {
+            self.open = !self.open;
+            return None;
+        }
+        if !self.open {
+            return None;
+        }
+
+        self.input.extend(
+            typed
+                .into_iter()
+                .filter(|character| !character.is_control() && *character != '`'),
+        );
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+        } else if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        } else if is_key_pressed(KeyCode::Up) && !self.history.is_empty() {
+            let index = self
+                .history_index
+                .map_or(self.history.len() - 1, |index| index.saturating_sub(1));
+            self.history_index = Some(index);
+            self.input = self.history[index].clone();
+        } else if is_key_pressed(KeyCode::Down) {
+            if let Some(index) = self.history_index {
+                if index + 1 < self.history.len() {
+                    self.history_index = Some(index + 1);
+                    self.input = self.history[index + 1].clone();
+                } else {
+                    self.history_index = None;
+                    self.input.clear();
+                }
+            }
+        } else if is_key_pressed(KeyCode::Enter) && !self.input.trim().is_empty() {
+            let line = std::mem::take(&mut self.input);
+            self.history_index = None;
+            if self.history.last() != Some(&line) {
+                self.history.push(line.clone());
+            }
+            self.print(format!("> {}", line));
+            return Some(line);
+        }
+        None
+    }
+
+    pub fn draw(&self) {
+        if !self.open {
+            return;
+        }
+        let height = (OUTPUT_LINES + 1) as f32 * LINE_HEIGHT + 10.0;
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.8),
+        );
+        for (index, line) in self.output.iter().enumerate() {
+            draw_text(
+                line,
+                10.0,
+                (index + 1) as f32 * LINE_HEIGHT,
+                FONT_SIZE,
+                LIGHTGRAY,
+            );
+        }
+        draw_text(
+            &format!("> {}_", self.input),
+            10.0,
+            height - 10.0,
+            FONT_SIZE,
+            WHITE,
+        );
+    }
+}
+
+impl Game {
+    pub fn run_command(&mut self, line: &str) {
+        let command = match ConsoleCommand::parse(line) {
+            Ok(command) => command,
+            Err(error) => {
+                self.console.print(error);
+                return;
+            }
+        };
+        let output = match command {
+            ConsoleCommand::Spawn {
+                kind,
+                count,
+                position,
+            } => {
+                let position = position.unwrap_or_else(|| {
+                    self.model.player_body().position + vec2(SPAWN_DISTANCE, 0.0)
+                });
+                self.model.spawn_enemies(kind, count, position);
+                format!("spawned {} {}", count, kind)
+            }
+            ConsoleCommand::Stage(stage) => {
+                self.model.skip_to_stage(stage);
+                format!("stage {}", self.model.current_stage)
+            }
+            ConsoleCommand::God => {
+                let god_mode = self.model.toggle_god_mode();
+                format!("god mode {}", if god_mode { "on" } else { "off" })
+            }
+            ConsoleCommand::KillAll => format!("killed {}", self.model.kill_enemies()),
+            ConsoleCommand::Set { name, value } => match self.model.set_value(&name, value) {
+                Ok(()) => format!("{} = {}", name, value),
+                Err(error) => error,
+            },
+            ConsoleCommand::TimeScale(scale) => {
+                self.time_scale = scale.clamp(0.0, TIME_SCALE_MAX);
+                format!("time scale {}", self.time_scale)
+            }
+            ConsoleCommand::Heal => {
+                self.model.heal_player();
+                "healed".to_owned()
+            }
+            ConsoleCommand::Seed(seed) => {
+                self.new_run(seed);
+                format!("new run with seed {}", seed)
+            }
+        };
+        self.console.print(output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            ConsoleCommand::parse("spawn ranger 5 at 10 -20"),
+            Ok(ConsoleCommand::Spawn {
+                kind: EnemyKind::Ranger,
+                count: 5,
+                position: Some(vec2(10.0, -20.0)),
+            })
+        );
+        assert_eq!(
+            ConsoleCommand::parse("spawn brute"),
+            Ok(ConsoleCommand::Spawn {
+                kind: EnemyKind::Brute,
+                count: 1,
+                position: None,
+            })
+        );
+        assert!(ConsoleCommand::parse("spawn dragon").is_err());
+        assert_eq!(
+            ConsoleCommand::parse("set chain_length 30"),
+            Ok(ConsoleCommand::Set {
+                name: "chain_length".to_owned(),
+                value: 30.0,
+            })
+        );
+        assert_eq!(
+            ConsoleCommand::parse("timescale 0.25"),
+            Ok(ConsoleCommand::TimeScale(0.25))
+        );
+    }
+
+    #[test]
+    fn rejects_non_finite_numbers() {
+        assert!(ConsoleCommand::parse("timescale inf").is_err());
+        assert!(ConsoleCommand::parse("timescale NaN").is_err());
+        assert!(ConsoleCommand::parse("set chain_length -inf").is_err());
+    }
+}
@@ -1,25 +1,63 @@
 use super::*;
 use macroquad::audio::{PlaySoundParams, Sound};
+use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, rc::Rc};
 
+mod achievements;
+mod audio;
+mod console;
 mod model;
 mod renderer;
+mod settings;
 
+use achievements::*;
+use audio::*;
+use console::*;
 use model::*;
 use renderer::*;
+use settings::*;
 
 const BACKGROUND_COLOR: Color = BLACK;
 const BORDER_COLOR: Color = GRAY;
 const MELEE_COLOR: Color = YELLOW;
 const RANGER_COLOR: Color = ORANGE;
 const BOMBER_COLOR: Color = WHITE;
+const LAUNCHER_COLOR: Color = PINK;
 const BOMB_COLOR: Color = RED;
+const MISSILE_COLOR: Color = MAGENTA;
+const SNIPER_COLOR: Color = LIME;
+const CHARGER_COLOR: Color = GOLD;
+const GUARDIAN_COLOR: Color = SKYBLUE;
+const BRUTE_COLOR: Color = BROWN;
+const BEAM_COLOR: Color = RED;
+const SHIELD_COLOR: Color = SKYBLUE;
+const SHOCKWAVE_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.3);
+const TELEGRAPH_COLOR: Color = Color::new(1.0, 0.3, 0.2, 1.0);
 const PROJECTILE_COLOR: Color = ORANGE;
 const SPAWNER_COLOR: Color = RED;
 const PLAYER_COLOR: Color = BLUE;
+const ENEMY_MARKER_COLOR: Color = RED;
+const HEAL_ZONE_COLOR: Color = GREEN;
 const PLAYER_BORDER_COLOR: Color = DARKBLUE;
 const PLAYER_LIFE_COLOR: Color = DARKBLUE;
 
+/// A frame this long means the game stalled, e.g. the window was minimized, dragged or throttled.
+/// Macroquad reports neither focus nor minimize events, so an unfocused window that keeps
+/// rendering is not paused.
+const AUTO_PAUSE_FRAME_TIME: f32 = 0.25;
+const TIME_SCALE_MIN: f32 = 1.0 / 16.0;
+const TIME_SCALE_MAX: f32 = 4.0;
+/// Toggle the matching entry of `Mutator::ALL` before a run
+const MUTATOR_KEYS: [KeyCode; 6] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+];
+const ROLL_MUTATORS_KEY: KeyCode = KeyCode::Key0;
+
 pub struct Assets {
     body_hit: Sound,
     head_hit: Sound,
@@ -28,18 +66,34 @@ pub struct Assets {
     explosion: Sound,
     music: Sound,
     tutorial: Texture2D,
+    sprites: Texture2D,
 }
 
 pub struct Game {
     renderer: Renderer,
     model: Model,
+    mixer: Mixer,
     assets: Rc<Assets>,
     last_mouse_position: Vec2,
     head_control_mode: HeadControlMode,
     state: GameState,
+    settings: Settings,
+    achievements: Achievements,
+    console: Console,
+    /// Scales simulation time, set from the console or the debug keys
+    time_scale: f32,
+    /// Settings were opened from the pause menu and return to it
+    paused_settings: bool,
+    /// Fixed ticks only run when requested with the step key
+    step_mode: bool,
+    pending_steps: usize,
+    /// Mutators picked on the tutorial screen, kept between runs
+    mutator_picks: Vec<Mutator>,
+    roll_mutators: bool,
 }
 
-enum HeadControlMode {
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum HeadControlMode {
     Mouse,
     Keys,
 }
@@ -49,11 +103,21 @@ pub enum GameState {
     Menu,
     Pregame,
     Game,
+    Paused,
+    Settings,
+    Achievements,
 }
 
 pub enum GameUpdate {
     Quit,
     Start,
+    Continue,
+    OpenSettings,
+    OpenAchievements,
+    BackToMenu,
+    Resume,
+    QuitToMenu,
+    ChangeSetting(SettingsChange),
 }
 
 impl Game {
@@ -68,53 +132,272 @@ impl Game {
             tutorial: macroquad::texture::load_texture("tutorial.png")
                 .await
                 .unwrap(),
+            sprites: macroquad::texture::load_texture("sprites.png")
+                .await
+                .unwrap(),
         });
         assets.tutorial.set_filter(FilterMode::Nearest);
-        let game = Self {
+        let settings = Settings::load();
+        let mut game = Self {
             renderer: Renderer::new(&assets),
-            model: Model::new(),
+            model: Model::new(macroquad::miniquad::date::now() as u64),
+            mixer: Mixer::new(&assets),
             assets,
             last_mouse_position: vec2(0.0, 0.0),
-            head_control_mode: HeadControlMode::Keys,
+            head_control_mode: settings.head_control_mode,
             state: GameState::Menu,
+            settings,
+            achievements: Achievements::load(),
+            console: Console::default(),
+            time_scale: 1.0,
+            paused_settings: false,
+            step_mode: false,
+            pending_steps: 0,
+            mutator_picks: vec![],
+            roll_mutators: false,
         };
+        for line in Console::startup_script() {
+            game.run_command(&line);
+        }
         macroquad::audio::play_sound(
-            game.assets.music.clone(),
+            game.assets.music,
             PlaySoundParams {
                 looped: true,
-                volume: 0.05,
+                volume: game.settings.music_volume(),
             },
         );
+        set_fullscreen(game.settings.fullscreen);
+        game.renderer.set_can_continue(Model::has_save());
+        game.renderer
+            .set_mutator_picks(&game.mutator_picks, game.roll_mutators);
         game
     }
 
+    /// How fast simulation time passes, zero while paused or stepping
+    pub fn time_scale(&self) -> f32 {
+        if self.paused() || self.step_mode {
+            0.0
+        } else {
+            self.time_scale
+        }
+    }
+
+    fn paused(&self) -> bool {
+        matches!(self.state, GameState::Paused) || self.paused_settings
+    }
+
+    /// Returns true once for every fixed tick requested in step mode
+    pub fn take_step(&mut self) -> bool {
+        if self.pending_steps > 0 && !self.paused() {
+            self.pending_steps -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn update(&mut self, delta_time: f32) {
-        if let Some(update) = self.renderer.update(delta_time, self.state, &self.model) {
+        let console_was_open = self.console.open;
+        if let Some(line) = self.console.update() {
+            self.run_command(&line);
+        }
+        let console_input = console_was_open || self.console.open;
+        if !console_input {
+            self.time_controls(delta_time);
+        }
+        self.renderer.set_ui_input(!console_input);
+        if let Some(update) =
+            self.renderer
+                .update(delta_time, self.state, &self.model, &self.settings)
+        {
             match update {
                 GameUpdate::Quit => unimplemented!(),
-                GameUpdate::Start => self.state = GameState::Pregame,
+                GameUpdate::Start => {
+                    self.model
+                        .set_difficulty(self.settings.difficulty, self.settings.dynamic_difficulty);
+                    self.state = GameState::Pregame;
+                }
+                GameUpdate::Continue => self.continue_game(),
+                GameUpdate::OpenSettings => {
+                    self.paused_settings = self.paused();
+                    self.state = GameState::Settings;
+                }
+                GameUpdate::OpenAchievements => {
+                    self.renderer.show_achievements(&self.achievements);
+                    self.state = GameState::Achievements;
+                }
+                GameUpdate::BackToMenu if self.paused_settings => {
+                    self.paused_settings = false;
+                    self.state = GameState::Paused;
+                }
+                GameUpdate::BackToMenu => self.state = GameState::Menu,
+                GameUpdate::Resume => self.state = GameState::Game,
+                GameUpdate::QuitToMenu => self.quit_to_menu(),
+                GameUpdate::ChangeSetting(change) => self.change_setting(change),
             }
         }
+        self.model.view = Some(self.renderer.camera.view_bounds());
         match self.state {
-            GameState::Menu => {
+            GameState::Paused => (),
+            GameState::Settings if self.paused_settings => (),
+            GameState::Menu | GameState::Settings | GameState::Achievements => {
                 self.control_head();
             }
+            // The key that opens or closes the console shouldn't pick a mutator or start the run
+            GameState::Pregame if console_input => (),
             GameState::Pregame => {
                 self.control_head();
-                if get_last_key_pressed().is_some() {
-                    self.state = GameState::Game;
+                if let Some(key) = get_last_key_pressed() {
+                    self.pick_mutators(key);
                 }
             }
             GameState::Game => {
-                self.control_head();
-                self.control_body();
-                self.model.update(delta_time);
+                if self.console.open {
+                    // Held movement keys would keep the player walking while typing
+                    self.model.move_direction(Vec2::ZERO);
+                } else {
+                    self.control_head();
+                    self.control_body();
+                }
+                if !self.model.player_alive() && !self.console.open {
+                    if is_key_pressed(KeyCode::R) {
+                        self.new_run(macroquad::miniquad::date::now() as u64);
+                    } else if is_key_pressed(KeyCode::E) {
+                        self.model.stats.export();
+                    }
+                }
             }
         }
 
+        self.renderer.set_mouse_control(self.paused());
+        self.mixer.update(delta_time);
         self.events();
     }
 
+    /// Pausing, plus slow motion and frame stepping while debug mode (F6) is on
+    fn time_controls(&mut self, delta_time: f32) {
+        match self.state {
+            GameState::Game
+                if is_key_pressed(KeyCode::Escape)
+                    || delta_time > AUTO_PAUSE_FRAME_TIME && self.model.player_alive() =>
+            {
+                self.state = GameState::Paused;
+            }
+            GameState::Paused if is_key_pressed(KeyCode::Escape) => {
+                self.state = GameState::Game;
+            }
+            _ => (),
+        }
+
+        if !self.renderer.debug_mode() {
+            return;
+        }
+        if is_key_pressed(KeyCode::Minus) {
+            self.time_scale = (self.time_scale / 2.0).max(TIME_SCALE_MIN);
+            self.renderer
+                .show_toast(format!("TIME SCALE: {}", self.time_scale));
+        } else if is_key_pressed(KeyCode::Equal) {
+            self.time_scale = (self.time_scale * 2.0).min(TIME_SCALE_MAX);
+            self.renderer
+                .show_toast(format!("TIME SCALE: {}", self.time_scale));
+        }
+        if is_key_pressed(KeyCode::F10) {
+            self.step_mode = !self.step_mode;
+            self.pending_steps = 0;
+            self.renderer.show_toast(format!(
+                "FRAME STEP: {}",
+                if self.step_mode { "ON" } else { "OFF" }
+            ));
+        } else if self.step_mode && is_key_pressed(KeyCode::Period) {
+            self.pending_steps += 1;
+        }
+    }
+
+    /// Number keys toggle mutators, any other key starts the run
+    fn pick_mutators(&mut self, key: KeyCode) {
+        if let Some(index) = MUTATOR_KEYS
+            .iter()
+            .position(|&mutator_key| mutator_key == key)
+        {
+            let mutator = Mutator::ALL[index];
+            match self.mutator_picks.iter().position(|&pick| pick == mutator) {
+                Some(pick) => {
+                    self.mutator_picks.remove(pick);
+                }
+                None => self.mutator_picks.push(mutator),
+            }
+        } else if key == ROLL_MUTATORS_KEY {
+            self.roll_mutators = !self.roll_mutators;
+        } else {
+            for &mutator in &self.mutator_picks {
+                self.model.add_mutator(mutator);
+            }
+            self.model.roll_mutators = self.roll_mutators;
+            self.state = GameState::Game;
+        }
+        self.renderer
+            .set_mutator_picks(&self.mutator_picks, self.roll_mutators);
+    }
+
+    /// Throws away the current run and starts picking mutators for a fresh one
+    fn new_run(&mut self, seed: u64) {
+        self.model = Model::new(seed);
+        self.model
+            .set_difficulty(self.settings.difficulty, self.settings.dynamic_difficulty);
+        self.achievements.start_run();
+        self.state = GameState::Pregame;
+    }
+
+    fn continue_game(&mut self) {
+        match Model::load() {
+            Some(model) => {
+                self.model = model;
+                self.achievements.start_run();
+                self.renderer.next_wave(self.model.current_stage);
+                self.state = GameState::Game;
+            }
+            None => self.renderer.set_can_continue(false),
+        }
+    }
+
+    pub fn quit(&mut self) {
+        if let GameState::Game | GameState::Paused = self.state {
+            if self.model.player_alive() {
+                self.model.save();
+            }
+        }
+    }
+
+    fn quit_to_menu(&mut self) {
+        if self.model.player_alive() {
+            self.model.save();
+            self.renderer.set_can_continue(true);
+        }
+        self.model = Model::new(macroquad::miniquad::date::now() as u64);
+        self.achievements.start_run();
+        self.state = GameState::Menu;
+    }
+
+    fn change_setting(&mut self, change: SettingsChange) {
+        self.settings.change(change);
+        match change {
+            SettingsChange::MasterVolume(_)
+            | SettingsChange::MusicVolume(_)
+            | SettingsChange::Mute => {
+                macroquad::audio::set_sound_volume(self.assets.music, self.settings.music_volume());
+            }
+            SettingsChange::Fullscreen => set_fullscreen(self.settings.fullscreen),
+            SettingsChange::HeadControlMode => {
+                self.head_control_mode = self.settings.head_control_mode
+            }
+            SettingsChange::SfxVolume(_)
+            | SettingsChange::ShowFps
+            | SettingsChange::Difficulty
+            | SettingsChange::DynamicDifficulty => (),
+        }
+        self.settings.save();
+    }
+
     fn control_body(&mut self) {
         let mut dir_x = 0.0;
         if is_key_down(KeyCode::A) {
@@ -159,8 +442,7 @@ impl Game {
                 direction += 1.0;
             }
             if direction != 0.0 {
-                let target =
-                    self.model.player.head.position - self.model.player.entity.rigidbody.position;
+                let target = self.model.player.head.position - self.model.player_body().position;
                 let target = vec2(target.y, -target.x).normalize() * direction * 5.0
                     + self.model.player.head.position;
                 self.model.head_target(target);
@@ -179,28 +461,62 @@ impl Game {
 
     fn events(&mut self) {
         let events = std::mem::take(&mut self.model.events);
-        for event in events {
+        let camera = &self.renderer.game_camera;
+        self.mixer.listener = Listener {
+            position: camera.target,
+            half_width: 1.0 / camera.zoom.x,
+        };
+        self.mixer.volume = self.settings.sfx_volume();
+
+        let mut handlers: Vec<&mut dyn EventHandler> = vec![&mut self.mixer, &mut self.renderer];
+        if !self.model.stats.cheated {
+            handlers.push(&mut self.achievements);
+        }
+        for event in &events {
+            for handler in &mut handlers {
+                handler.handle_event(event);
+            }
+        }
+
+        for event in &events {
             match event {
-                Event::Sound { sound } => {
-                    let sound = match sound {
-                        EventSound::HeadHit => self.assets.head_hit.clone(),
-                        EventSound::BodyHit => self.assets.body_hit.clone(),
-                        EventSound::Death => self.assets.death.clone(),
-                        EventSound::Bounce => self.assets.bounce.clone(),
-                        EventSound::Explosion => self.assets.explosion.clone(),
-                    };
-                    macroquad::audio::play_sound_once(sound);
+                Event::NextWave { .. } => {
+                    self.model.save();
+                    self.renderer.set_can_continue(true);
+                }
+                Event::PlayerDied { .. } => {
+                    Model::delete_save();
+                    self.renderer.set_can_continue(false);
                 }
-                Event::NextWave { stage } => self.renderer.next_wave(stage),
+                _ => (),
             }
         }
+
+        if !self.model.stats.cheated {
+            self.achievements.check_stats(&self.model.stats);
+        }
+        let unlocks = self.achievements.take_new_unlocks();
+        for achievement in &unlocks {
+            self.renderer
+                .show_toast(format!("ACHIEVEMENT UNLOCKED: {}", achievement.name()));
+        }
+        if !unlocks.is_empty() {
+            self.achievements.save();
+        }
     }
 
     pub fn fixed_update(&mut self, delta_time: f32) {
-        self.model.fixed_update(delta_time);
+        match self.state {
+            GameState::Game => self.model.fixed_update(delta_time),
+            _ => self.model.idle_update(delta_time),
+        }
     }
 
-    pub fn draw(&mut self) {
-        self.renderer.draw(&self.model);
+    /// `alpha` is how far the frame is between the last two fixed ticks
+    pub fn draw(&mut self, alpha: f32) {
+        // Stepped ticks are shown exactly as simulated
+        let alpha = if self.step_mode { 1.0 } else { alpha };
+        self.renderer.draw(&self.model, alpha);
+        self.console.draw();
     }
 }
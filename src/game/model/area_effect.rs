@@ -1,12 +1,15 @@
 use super::*;
 
+#[derive(Serialize, Deserialize)]
 pub struct AreaEffect {
     pub position: Vec2,
     pub radius: f32,
     pub effect: Effect,
     pub lifetime: Health,
+    pub collected: bool,
 }
 
+#[derive(Serialize, Deserialize)]
 pub enum Effect {
     Heal { heal: f32 },
 }
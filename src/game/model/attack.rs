@@ -1,87 +1,309 @@
 use super::*;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Attack {
     pub attack_time: Health,
     pub attack_type: AttackType,
+    pub telegraph: Option<Telegraph>,
 }
 
-#[derive(Clone)]
+/// A wind-up shown in the world before the attack fires
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Telegraph {
+    pub wind_up: f32,
+    pub shape: TelegraphShape,
+    pub cancel_strength: f32,
+    pub state: Option<WindUp>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum TelegraphShape {
+    Line { length: f32, width: f32 },
+    Cone { length: f32, angle: f32 },
+    Circle { radius: f32 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindUp {
+    pub time_left: f32,
+    pub direction: Vec2,
+}
+
+impl Telegraph {
+    pub fn new(wind_up: f32, shape: TelegraphShape, cancel_strength: f32) -> Self {
+        Self {
+            wind_up,
+            shape,
+            cancel_strength,
+            state: None,
+        }
+    }
+
+    pub fn progress(&self) -> Option<f32> {
+        self.state
+            .map(|wind_up| 1.0 - (wind_up.time_left / self.wind_up).clamp(0.0, 1.0))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum AttackType {
     Shoot {
-        projectile: Box<dyn EntityObjectInfo>,
+        projectile: Box<EntityBlueprint>,
         target_pos: Vec2,
     },
     Explode {
-        projectile: Box<dyn EntityObjectInfo>,
+        projectile: Box<EntityBlueprint>,
         projectile_count: usize,
     },
     Drop {
-        drop: Box<dyn EntityObjectInfo>,
+        drop: Box<EntityBlueprint>,
+    },
+    Beam {
+        target_pos: Vec2,
+        range: f32,
+        width: f32,
+        damage: f32,
+    },
+    Dash {
+        target_pos: Vec2,
+        speed: f32,
+        duration: f32,
+        mass: f32,
+    },
+    Shield {
+        target_pos: Vec2,
+        arc: f32,
+        radius: f32,
+        duration: f32,
+    },
+    Shockwave {
+        shockwave: ExplosionInfo,
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AttackKind {
+    Shoot,
+    Explode,
+    Drop,
+    Beam,
+    Dash,
+    Shield,
+    Shockwave,
+}
+
+impl AttackType {
+    pub fn kind(&self) -> AttackKind {
+        match self {
+            AttackType::Shoot { .. } => AttackKind::Shoot,
+            AttackType::Explode { .. } => AttackKind::Explode,
+            AttackType::Drop { .. } => AttackKind::Drop,
+            AttackType::Beam { .. } => AttackKind::Beam,
+            AttackType::Dash { .. } => AttackKind::Dash,
+            AttackType::Shield { .. } => AttackKind::Shield,
+            AttackType::Shockwave { .. } => AttackKind::Shockwave,
+        }
+    }
+
+    pub fn target(&self) -> Option<Vec2> {
+        match self {
+            AttackType::Shoot { target_pos, .. }
+            | AttackType::Beam { target_pos, .. }
+            | AttackType::Dash { target_pos, .. }
+            | AttackType::Shield { target_pos, .. } => Some(*target_pos),
+            AttackType::Explode { .. } | AttackType::Drop { .. } | AttackType::Shockwave { .. } => {
+                None
+            }
+        }
+    }
+
+    pub fn set_target(&mut self, target: Vec2) {
+        match self {
+            AttackType::Shoot { target_pos, .. }
+            | AttackType::Beam { target_pos, .. }
+            | AttackType::Dash { target_pos, .. }
+            | AttackType::Shield { target_pos, .. } => *target_pos = target,
+            AttackType::Explode { .. } | AttackType::Drop { .. } | AttackType::Shockwave { .. } => {
+            }
+        }
+    }
+}
+
 impl Attack {
-    pub fn perform(&mut self, entity: &mut Entity, commands: &mut Commands) {
+    pub fn tick(&mut self, delta_time: f32) {
+        self.attack_time.change(-delta_time);
+        if let Some(Telegraph {
+            state: Some(wind_up),
+            ..
+        }) = &mut self.telegraph
+        {
+            wind_up.time_left -= delta_time;
+        }
+    }
+
+    /// Stops the wind-up if the hit is strong enough, returns whether it did
+    pub fn interrupt(&mut self, strength: f32) -> bool {
+        match &mut self.telegraph {
+            Some(telegraph)
+                if telegraph.state.is_some() && strength >= telegraph.cancel_strength =>
+            {
+                telegraph.state = None;
+                self.attack_time.hp = self.attack_time.hp_max;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn charge_color(&self) -> Option<Color> {
         match &self.attack_type {
+            AttackType::Explode { .. } if self.attack_time.is_alive() => {
+                let time_frac = self.attack_time.hp_frac();
+                Some(Color::new(
+                    (BOMB_COLOR.r - BOMBER_COLOR.r) * (1.0 - time_frac) + BOMBER_COLOR.r,
+                    (BOMB_COLOR.g - BOMBER_COLOR.g) * (1.0 - time_frac) + BOMBER_COLOR.g,
+                    (BOMB_COLOR.b - BOMBER_COLOR.b) * (1.0 - time_frac) + BOMBER_COLOR.b,
+                    1.0,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn perform(&mut self, id: EntityId, position: Vec2, commands: &mut Commands) {
+        if let Some(telegraph) = &mut self.telegraph {
+            match telegraph.state {
+                None => {
+                    if !self.attack_time.is_alive() {
+                        // The aim is locked when the wind-up starts, so the attack can be dodged
+                        let direction = self
+                            .attack_type
+                            .target()
+                            .map_or(Vec2::ZERO, |target| (target - position).normalize_or_zero());
+                        telegraph.state = Some(WindUp {
+                            time_left: telegraph.wind_up,
+                            direction,
+                        });
+                    }
+                    return;
+                }
+                Some(wind_up) if wind_up.time_left > 0.0 => return,
+                Some(wind_up) => {
+                    telegraph.state = None;
+                    if wind_up.direction != Vec2::ZERO {
+                        self.attack_type.set_target(position + wind_up.direction);
+                    }
+                    self.attack_time.kill();
+                }
+            }
+        }
+
+        let fired = !self.attack_time.is_alive();
+        match &mut self.attack_type {
             AttackType::Shoot {
                 projectile,
                 target_pos,
             } => {
                 if !self.attack_time.is_alive() {
-                    let mut projectile = projectile
-                        .clone()
-                        .into_entity_object(entity.rigidbody.position);
-                    projectile.entity_mut().rigidbody.velocity =
-                        (*target_pos - projectile.entity().rigidbody.position).normalize()
-                            * projectile.entity().movement_speed;
-                    commands.spawn_entity(projectile);
+                    let velocity =
+                        (*target_pos - position).normalize() * projectile.movement_speed();
+                    commands.spawn_entity((**projectile).clone(), position, velocity, Some(id));
                 }
             }
             AttackType::Explode {
                 projectile,
                 projectile_count,
             } => {
-                if self.attack_time.is_alive() {
-                    let time_frac = self.attack_time.hp_frac();
-                    entity.color = Color::new(
-                        (BOMB_COLOR.r - BOMBER_COLOR.r) * (1.0 - time_frac) + BOMBER_COLOR.r,
-                        (BOMB_COLOR.g - BOMBER_COLOR.g) * (1.0 - time_frac) + BOMBER_COLOR.g,
-                        (BOMB_COLOR.b - BOMBER_COLOR.b) * (1.0 - time_frac) + BOMBER_COLOR.b,
-                        1.0,
-                    );
-                } else {
-                    let random_offset = macroquad::rand::gen_range(0.0, std::f32::consts::PI);
+                if !self.attack_time.is_alive() {
+                    let random_offset = commands.rng.gen_range(0.0, std::f32::consts::PI);
                     for i in 0..*projectile_count {
-                        let mut projectile = projectile
-                            .clone()
-                            .into_entity_object(entity.rigidbody.position);
                         let angle = (i as f32) * std::f32::consts::PI * 2.0
                             / (*projectile_count as f32)
                             + random_offset;
                         let (sin, cos) = angle.sin_cos();
-                        projectile.entity_mut().rigidbody.velocity =
-                            vec2(cos, sin) * projectile.entity().movement_speed;
-                        commands.spawn_entity(projectile);
+                        let velocity = vec2(cos, sin) * projectile.movement_speed();
+                        commands.spawn_entity((**projectile).clone(), position, velocity, Some(id));
                     }
-                    entity.destroy = true;
-                    commands.spawn_particles(entity.rigidbody.position, 500.0, BOMB_COLOR);
+                    commands.despawn(id);
+                    commands.spawn_particles(
+                        ParticlePreset::Explosion,
+                        position,
+                        EXPLOSION_INTENSITY,
+                        BOMB_COLOR,
+                    );
                     commands.event(Event::Sound {
                         sound: EventSound::Explosion,
+                        position,
+                        intensity: EXPLOSION_INTENSITY,
                     });
                 }
             }
 
             AttackType::Drop { drop } => {
                 if !self.attack_time.is_alive() {
-                    let drop = drop.clone().into_entity_object(entity.rigidbody.position);
-                    commands.spawn_entity(drop);
+                    commands.spawn_entity((**drop).clone(), position, Vec2::ZERO, Some(id));
+                    commands.event(Event::Sound {
+                        sound: EventSound::Explosion,
+                        position,
+                        intensity: EXPLOSION_INTENSITY,
+                    });
+                }
+            }
+            AttackType::Beam {
+                target_pos,
+                range,
+                width,
+                damage,
+            } => {
+                if !self.attack_time.is_alive() {
+                    let end = position + (*target_pos - position).normalize_or_zero() * *range;
+                    commands.hitscan(id, position, end, *width, *damage);
+                }
+            }
+            AttackType::Dash {
+                target_pos,
+                speed,
+                duration,
+                mass,
+            } => {
+                if !self.attack_time.is_alive() {
+                    let velocity = (*target_pos - position).normalize_or_zero() * *speed;
+                    commands.dash(id, velocity, *mass, *duration);
+                }
+            }
+            AttackType::Shield {
+                target_pos,
+                arc,
+                radius,
+                duration,
+            } => {
+                if !self.attack_time.is_alive() {
+                    let direction = (*target_pos - position).normalize_or_zero();
+                    commands.shield(id, direction, *arc, *radius, *duration);
+                }
+            }
+            AttackType::Shockwave { shockwave } => {
+                if !self.attack_time.is_alive() {
+                    commands.spawn_entity(
+                        EntityBlueprint::Explosion(shockwave.clone()),
+                        position,
+                        Vec2::ZERO,
+                        Some(id),
+                    );
                     commands.event(Event::Sound {
                         sound: EventSound::Explosion,
+                        position,
+                        intensity: shockwave.hit_strength,
                     });
                 }
             }
         }
+        if fired {
+            commands.event(Event::AttackFired {
+                attack_kind: self.attack_type.kind(),
+                position,
+            });
+        }
         if !self.attack_time.is_alive() {
             self.attack_time.hp = self.attack_time.hp_max;
         }
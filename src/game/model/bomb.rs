@@ -1,62 +1,11 @@
 use super::*;
 
-pub struct Bomb {
-    entity: Entity,
-    entity_type: EntityType,
-    lifetime: Health,
-    attack: bool,
-    impact_attack: Attack,
-}
-
-impl Bomb {
-    fn new(position: Vec2, bomb_info: BombInfo) -> Self {
-        Self {
-            entity: Entity::new(position, bomb_info.entity_info),
-            entity_type: bomb_info.entity_type,
-            lifetime: bomb_info.lifetime,
-            attack: false,
-            impact_attack: Attack {
-                attack_time: Health::new(0.0),
-                attack_type: AttackType::Drop {
-                    drop: bomb_info.drop,
-                },
-            },
-        }
-    }
-}
-
-impl EntityObject for Bomb {
-    fn entity_mut(&mut self) -> &mut Entity {
-        &mut self.entity
-    }
-
-    fn entity(&self) -> &Entity {
-        &self.entity
-    }
-
-    fn entity_type(&self) -> EntityType {
-        self.entity_type
-    }
-
-    fn attack(&mut self, _: Option<Vec2>, delta_time: f32, commands: &mut Commands) {
-        self.lifetime.change(-delta_time);
-        if self.attack || !self.lifetime.is_alive() {
-            self.entity.destroy = true;
-            self.impact_attack.perform(&mut self.entity, commands);
-        }
-    }
-
-    fn on_collide(&mut self, _: &mut Commands) {
-        self.attack = true;
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BombInfo {
-    entity_info: EntityInfo,
-    entity_type: EntityType,
-    lifetime: Health,
-    drop: Box<dyn EntityObjectInfo>,
+    pub entity_info: EntityInfo,
+    pub entity_type: EntityType,
+    pub lifetime: Health,
+    pub drop: Box<EntityBlueprint>,
 }
 
 impl BombInfo {
@@ -64,19 +13,43 @@ impl BombInfo {
         entity_info: EntityInfo,
         entity_type: EntityType,
         lifetime: Health,
-        drop: Box<dyn EntityObjectInfo>,
+        drop: EntityBlueprint,
     ) -> Self {
         Self {
             entity_info,
             entity_type,
             lifetime,
-            drop,
+            drop: Box::new(drop),
         }
     }
-}
 
-impl EntityObjectInfo for BombInfo {
-    fn into_entity_object(self: Box<Self>, position: Vec2) -> Box<dyn EntityObject> {
-        Box::new(Bomb::new(position, *self))
+    pub fn spawn(self, position: Vec2, store: &mut EntityStore) -> EntityId {
+        let id = store.spawn(
+            position,
+            EntityKind::Bomb,
+            self.entity_type,
+            self.entity_info,
+        );
+        store.lifetimes.insert(
+            id,
+            Lifetime {
+                time: self.lifetime,
+                on_expire: Expire::Trigger,
+                expire_on_bounds: false,
+            },
+        );
+        store.impacts.insert(
+            id,
+            Impact {
+                attack: Attack {
+                    attack_time: Health::new(0.0),
+                    telegraph: None,
+                    attack_type: AttackType::Drop { drop: self.drop },
+                },
+                triggered: false,
+                trigger_on_bounds: false,
+            },
+        );
+        id
     }
 }
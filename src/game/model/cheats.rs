@@ -0,0 +1,65 @@
+use super::*;
+
+/// Radius of the group spawned by the console
+const CHEAT_GROUP_RADIUS: f32 = 10.0;
+
+impl Model {
+    pub fn spawn_enemies(&mut self, kind: EnemyKind, count: usize, position: Vec2) {
+        self.stats.cheated = true;
+        let group = WaveGroup {
+            entities: (0..count).map(|_| self.scaled_enemy(kind)).collect(),
+            radius: CHEAT_GROUP_RADIUS,
+        };
+        self.spawn_group(position, group);
+    }
+
+    /// Clears the current wave and starts the given stage
+    pub fn skip_to_stage(&mut self, stage: usize) {
+        self.stats.cheated = true;
+        self.kill_enemies();
+        self.spawners.clear();
+        self.current_stage = stage.max(1) - 1;
+        let mut commands = Commands::new(self.rng.fork());
+        self.next_wave(&mut commands);
+        self.perform_commands(commands);
+    }
+
+    pub fn kill_enemies(&mut self) -> usize {
+        self.stats.cheated = true;
+        let enemies: Vec<EntityId> = self.faction_alive(EntityType::Enemy);
+        for &id in &enemies {
+            self.store.kill(id, DamageSource::Expired);
+        }
+        enemies.len()
+    }
+
+    pub fn heal_player(&mut self) {
+        self.stats.cheated = true;
+        if let Some(health) = self.store.healths.get_mut(self.player.id) {
+            health.hp = health.hp_max;
+        }
+    }
+
+    pub fn toggle_god_mode(&mut self) -> bool {
+        self.stats.cheated = true;
+        self.god_mode = !self.god_mode;
+        self.god_mode
+    }
+
+    /// Changes a tunable value by name
+    pub fn set_value(&mut self, name: &str, value: f32) -> Result<(), String> {
+        let target = match name {
+            "chain_length" => &mut self.player.chain_length,
+            "stun_threshold" => &mut self.impact_tuning.stun_threshold,
+            "stun_per_strength" => &mut self.impact_tuning.stun_per_strength,
+            "stun_max" => &mut self.impact_tuning.stun_max,
+            "hit_stop_threshold" => &mut self.impact_tuning.hit_stop_threshold,
+            "hit_stop_per_strength" => &mut self.impact_tuning.hit_stop_per_strength,
+            "hit_stop_max" => &mut self.impact_tuning.hit_stop_max,
+            _ => return Err(format!("unknown value: {}", name)),
+        };
+        *target = value;
+        self.stats.cheated = true;
+        Ok(())
+    }
+}
@@ -3,39 +3,128 @@ use super::*;
 pub struct Commands {
     commands: Vec<Command>,
     pub events: Vec<Event>,
+    pub rng: Rng,
 }
 
 enum Command {
     SpawnEntity {
-        entity: Box<dyn EntityObject>,
-    },
-    SpawnParticles {
+        blueprint: EntityBlueprint,
         position: Vec2,
-        intensity: f32,
-        color: Color,
+        velocity: Vec2,
+        owner: Option<EntityId>,
+    },
+    Despawn {
+        id: EntityId,
+    },
+    EmitParticles {
+        emitter: ParticleEmitter,
+    },
+    Hitscan {
+        owner: EntityId,
+        origin: Vec2,
+        end: Vec2,
+        width: f32,
+        damage: f32,
+    },
+    Dash {
+        id: EntityId,
+        velocity: Vec2,
+        mass: f32,
+        duration: f32,
+    },
+    Shield {
+        id: EntityId,
+        direction: Vec2,
+        arc: f32,
+        radius: f32,
+        duration: f32,
+    },
+    AddMutator {
+        mutator: Mutator,
     },
 }
 
 impl Commands {
-    pub fn new() -> Self {
+    pub fn new(rng: Rng) -> Self {
         Self {
             commands: vec![],
             events: vec![],
+            rng,
         }
     }
 
-    pub fn spawn_entity(&mut self, entity: Box<dyn EntityObject>) {
-        self.commands.push(Command::SpawnEntity { entity });
+    pub fn spawn_entity(
+        &mut self,
+        blueprint: EntityBlueprint,
+        position: Vec2,
+        velocity: Vec2,
+        owner: Option<EntityId>,
+    ) {
+        self.commands.push(Command::SpawnEntity {
+            blueprint,
+            position,
+            velocity,
+            owner,
+        });
     }
 
-    pub fn spawn_particles(&mut self, position: Vec2, intensity: f32, color: Color) {
-        self.commands.push(Command::SpawnParticles {
-            position,
-            intensity,
-            color,
+    pub fn despawn(&mut self, id: EntityId) {
+        self.commands.push(Command::Despawn { id });
+    }
+
+    pub fn spawn_particles(
+        &mut self,
+        preset: ParticlePreset,
+        position: Vec2,
+        intensity: f32,
+        color: Color,
+    ) {
+        self.emit_particles(ParticleEmitter::new(preset, position, intensity, color));
+    }
+
+    pub fn emit_particles(&mut self, emitter: ParticleEmitter) {
+        self.commands.push(Command::EmitParticles { emitter });
+    }
+
+    pub fn hitscan(&mut self, owner: EntityId, origin: Vec2, end: Vec2, width: f32, damage: f32) {
+        self.commands.push(Command::Hitscan {
+            owner,
+            origin,
+            end,
+            width,
+            damage,
+        });
+    }
+
+    pub fn dash(&mut self, id: EntityId, velocity: Vec2, mass: f32, duration: f32) {
+        self.commands.push(Command::Dash {
+            id,
+            velocity,
+            mass,
+            duration,
         });
     }
 
+    pub fn shield(&mut self, id: EntityId, direction: Vec2, arc: f32, radius: f32, duration: f32) {
+        self.commands.push(Command::Shield {
+            id,
+            direction,
+            arc,
+            radius,
+            duration,
+        });
+    }
+
+    pub fn add_mutator(&mut self, mutator: Mutator) {
+        self.commands.push(Command::AddMutator { mutator });
+    }
+
+    pub fn is_despawning(&self, id: EntityId) -> bool {
+        self.commands
+            .iter()
+            .any(|command| matches!(command, Command::Despawn { id: despawn } if *despawn == id))
+    }
+
     pub fn event(&mut self, event: Event) {
         self.events.push(event);
     }
@@ -43,22 +132,85 @@ impl Commands {
 
 impl Model {
     pub fn perform_commands(&mut self, commands: Commands) {
-        for command in commands.commands {
+        let Commands {
+            commands,
+            mut events,
+            ..
+        } = commands;
+        for command in commands {
             match command {
-                Command::SpawnEntity { entity } => match entity.entity_type() {
-                    EntityType::Player => unimplemented!(),
-                    EntityType::Enemy => self.enemies.push(entity),
-                    EntityType::Minion => self.minions.push(entity),
-                },
-                Command::SpawnParticles {
+                Command::SpawnEntity {
+                    blueprint,
                     position,
-                    intensity,
-                    color,
+                    velocity,
+                    owner,
+                } => {
+                    let id = self.spawn_blueprint(blueprint, position);
+                    if let Some(rigidbody) = self.store.rigidbodies.get_mut(id) {
+                        rigidbody.velocity = velocity;
+                    }
+                    if let Some(Ai::Follow { leader, .. }) = self.store.ais.get_mut(id) {
+                        *leader = owner;
+                    }
+                }
+                Command::Despawn { id } => self.store.despawn(id),
+                Command::EmitParticles { emitter } => self.emit_particles(emitter),
+                Command::Hitscan {
+                    owner,
+                    origin,
+                    end,
+                    width,
+                    damage,
+                } => {
+                    self.hitscan(owner, origin, end, width, damage, &mut events);
+                }
+                Command::Dash {
+                    id,
+                    velocity,
+                    mass,
+                    duration,
+                } => {
+                    if let Some(rigidbody) = self.store.rigidbodies.get_mut(id) {
+                        let dash = self.store.dashes.get(id).cloned().unwrap_or(Dash {
+                            time_left: 0.0,
+                            mass: rigidbody.mass,
+                            is_kinematic: rigidbody.is_kinematic,
+                        });
+                        rigidbody.velocity = velocity;
+                        rigidbody.mass = mass;
+                        rigidbody.is_kinematic = true;
+                        self.store.dashes.insert(
+                            id,
+                            Dash {
+                                time_left: duration,
+                                ..dash
+                            },
+                        );
+                    }
+                }
+                Command::Shield {
+                    id,
+                    direction,
+                    arc,
+                    radius,
+                    duration,
                 } => {
-                    self.spawn_particles_hit(position, intensity, color);
+                    if !self.store.is_valid(id) {
+                        continue;
+                    }
+                    self.store.shields.insert(
+                        id,
+                        Shield {
+                            direction,
+                            arc,
+                            radius,
+                            time_left: duration,
+                        },
+                    );
                 }
+                Command::AddMutator { mutator } => self.apply_mutator(mutator, &mut events),
             }
         }
-        self.events.extend(commands.events);
+        self.events.extend(events);
     }
 }
@@ -0,0 +1,167 @@
+use super::*;
+
+const DYNAMIC_BUDGET_MIN: f32 = 0.6;
+const DYNAMIC_BUDGET_MAX: f32 = 1.5;
+const DYNAMIC_BUDGET_STEP: f32 = 0.1;
+/// Below this health fraction after a wave the next one gets smaller
+const STRUGGLING_HEALTH: f32 = 0.35;
+/// Above this health fraction after a wave the next one may get bigger
+const CRUISING_HEALTH: f32 = 0.7;
+const STRUGGLING_DAMAGE: f32 = 100.0;
+const CRUISING_DAMAGE: f32 = 25.0;
+const SLOW_CLEAR_TIME: f32 = 60.0;
+const FAST_CLEAR_TIME: f32 = 20.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+/// Multipliers applied to every wave
+#[derive(Debug, Clone, Copy)]
+pub struct DifficultyScaling {
+    pub health: f32,
+    pub speed: f32,
+    pub cooldown: f32,
+    pub spawn_count: f32,
+    pub heal: f32,
+}
+
+impl Difficulty {
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Nightmare,
+            Difficulty::Nightmare => Difficulty::Easy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+            Difficulty::Nightmare => "NIGHTMARE",
+        }
+    }
+
+    pub fn scaling(self) -> DifficultyScaling {
+        match self {
+            Difficulty::Easy => DifficultyScaling {
+                health: 0.75,
+                speed: 0.85,
+                cooldown: 1.3,
+                spawn_count: 0.75,
+                heal: 1.5,
+            },
+            Difficulty::Normal => DifficultyScaling {
+                health: 1.0,
+                speed: 1.0,
+                cooldown: 1.0,
+                spawn_count: 1.0,
+                heal: 1.0,
+            },
+            Difficulty::Hard => DifficultyScaling {
+                health: 1.25,
+                speed: 1.1,
+                cooldown: 0.85,
+                spawn_count: 1.25,
+                heal: 0.75,
+            },
+            Difficulty::Nightmare => DifficultyScaling {
+                health: 1.6,
+                speed: 1.25,
+                cooldown: 0.7,
+                spawn_count: 1.5,
+                heal: 0.5,
+            },
+        }
+    }
+}
+
+impl DifficultyScaling {
+    pub fn apply(&self, blueprint: &mut EntityBlueprint) {
+        if let EntityBlueprint::Enemy(info) = blueprint {
+            let entity_info = &mut info.entity_info;
+            entity_info.health = Health::new(entity_info.health.hp_max * self.health);
+            entity_info.movement_speed *= self.speed;
+            if let EnemyType::Attacker { attack } = &mut info.enemy_type {
+                attack.attack_time = Health::new(attack.attack_time.hp_max * self.cooldown);
+            }
+        }
+    }
+}
+
+/// Watches how the player handles each wave and resizes the next one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicDifficulty {
+    pub enabled: bool,
+    /// Multiplier on the size of the next wave
+    pub budget: f32,
+    /// Total damage taken when the current wave started
+    damage_at_wave_start: f32,
+}
+
+impl Default for DynamicDifficulty {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            budget: 1.0,
+            damage_at_wave_start: 0.0,
+        }
+    }
+}
+
+impl DynamicDifficulty {
+    pub fn wave_cleared(&mut self, health_frac: f32, total_damage: f32, clear_time: f32) {
+        let damage = total_damage - self.damage_at_wave_start;
+        self.damage_at_wave_start = total_damage;
+        if !self.enabled {
+            return;
+        }
+
+        let struggling = health_frac < STRUGGLING_HEALTH
+            || damage > STRUGGLING_DAMAGE
+            || clear_time > SLOW_CLEAR_TIME;
+        let cruising = health_frac > CRUISING_HEALTH
+            && damage < CRUISING_DAMAGE
+            && clear_time < FAST_CLEAR_TIME;
+        if struggling {
+            self.budget -= DYNAMIC_BUDGET_STEP;
+        } else if cruising {
+            self.budget += DYNAMIC_BUDGET_STEP;
+        }
+        self.budget = self.budget.clamp(DYNAMIC_BUDGET_MIN, DYNAMIC_BUDGET_MAX);
+        log_debug!(
+            "wave cleared in {:.1}s, {:.0} damage taken, next wave budget {:.1}",
+            clear_time,
+            damage,
+            self.budget
+        );
+    }
+}
+
+impl Model {
+    pub fn set_difficulty(&mut self, difficulty: Difficulty, dynamic: bool) {
+        self.difficulty = difficulty;
+        self.dynamic_difficulty.enabled = dynamic;
+        self.stats.difficulty = difficulty;
+    }
+
+    /// How many more enemies waves spawn than on normal
+    pub fn spawn_scale(&self) -> f32 {
+        self.difficulty.scaling().spawn_count * self.dynamic_difficulty.budget
+    }
+
+    /// An enemy blueprint scaled to the current difficulty
+    pub fn scaled_enemy(&self, kind: EnemyKind) -> EntityBlueprint {
+        let mut blueprint = enemy_blueprint(kind);
+        self.difficulty.scaling().apply(&mut blueprint);
+        blueprint
+    }
+}
@@ -1,112 +1,74 @@
-use core::f32;
-
 use super::*;
 
-pub struct Enemy {
-    pub entity: Entity,
-    pub enemy_type: EnemyType,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EnemyKind {
+    Melee,
+    Ranger,
+    Bomber,
+    Launcher,
+    Sniper,
+    Charger,
+    Guardian,
+    Brute,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 8] = [
+        EnemyKind::Melee,
+        EnemyKind::Ranger,
+        EnemyKind::Bomber,
+        EnemyKind::Launcher,
+        EnemyKind::Sniper,
+        EnemyKind::Charger,
+        EnemyKind::Guardian,
+        EnemyKind::Brute,
+    ];
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum EnemyType {
     Crawler,
     Attacker { attack: Attack },
 }
 
-impl Enemy {
-    pub fn new(position: Vec2, enemy_info: EnemyInfo) -> Self {
-        Self {
-            entity: Entity::new(position, enemy_info.entity_info),
-            enemy_type: enemy_info.enemy_type,
-        }
-    }
-}
-
-impl EntityObject for Enemy {
-    fn entity_mut(&mut self) -> &mut Entity {
-        &mut self.entity
-    }
-
-    fn entity(&self) -> &Entity {
-        &self.entity
-    }
-
-    fn entity_type(&self) -> EntityType {
-        EntityType::Enemy
-    }
-
-    fn attack_targets(&self) -> Vec<EntityType> {
-        vec![EntityType::Player]
-    }
-
-    fn attack(&mut self, target_pos: Option<Vec2>, delta_time: f32, commands: &mut Commands) {
-        match &mut self.enemy_type {
-            EnemyType::Attacker { attack } => {
-                attack.attack_time.change(-delta_time);
-                if let Some(target) = target_pos {
-                    match &mut attack.attack_type {
-                        AttackType::Shoot { target_pos, .. } => {
-                            *target_pos = target;
-                        }
-                        _ => (),
-                    }
-
-                    attack.perform(&mut self.entity, commands);
-                }
-            }
-            _ => (),
-        }
-    }
-
-    fn movement_targets(&self) -> Vec<EntityType> {
-        vec![EntityType::Player]
-    }
-
-    fn decide_movement(&mut self, target_pos: Option<Vec2>, delta_time: f32) {
-        if let Some(target_pos) = target_pos {
-            match &self.enemy_type {
-                EnemyType::Crawler | EnemyType::Attacker { .. } => {
-                    let target_direction = target_pos - self.entity.rigidbody.position;
-                    let target_velocity = target_direction.normalize() * self.entity.movement_speed;
-                    self.entity.rigidbody.velocity +=
-                        (target_velocity - self.entity.rigidbody.velocity) * delta_time;
-                }
-            }
-        }
-    }
-
-    fn dead(&mut self, _: f32) -> DeadState {
-        let mut destroy = DeadState::Corpse;
-        match &mut self.enemy_type {
-            EnemyType::Attacker { attack } if !attack.attack_time.is_alive() => {
-                match attack.attack_type {
-                    AttackType::Explode { .. } => destroy = DeadState::Destroy,
-                    _ => (),
-                }
-            }
-            _ => (),
-        }
-        destroy
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct EnemyInfo {
+    pub enemy_kind: EnemyKind,
     pub enemy_type: EnemyType,
     pub entity_info: EntityInfo,
 }
 
 impl EnemyInfo {
-    pub fn new(enemy_type: EnemyType, entity_info: EntityInfo) -> Self {
+    pub fn new(enemy_kind: EnemyKind, enemy_type: EnemyType, entity_info: EntityInfo) -> Self {
         Self {
+            enemy_kind,
             enemy_type,
             entity_info,
         }
     }
-}
 
-impl EntityObjectInfo for EnemyInfo {
-    fn into_entity_object(self: Box<Self>, position: Vec2) -> Box<dyn EntityObject> {
-        Box::new(Enemy::new(position, *self))
+    pub fn spawn(self, position: Vec2, store: &mut EntityStore) -> EntityId {
+        let id = store.spawn(
+            position,
+            EntityKind::Enemy(self.enemy_kind),
+            EntityType::Enemy,
+            self.entity_info,
+        );
+        store.ais.insert(
+            id,
+            Ai::Chase {
+                targets: vec![EntityType::Player],
+            },
+        );
+        if let EnemyType::Attacker { attack } = self.enemy_type {
+            store.attacks.insert(
+                id,
+                Attacker {
+                    attack,
+                    targets: vec![EntityType::Player],
+                },
+            );
+        }
+        id
     }
 }
@@ -1,8 +1,55 @@
+use super::*;
+
+// Subscribers only read the fields they care about
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub enum Event {
-    NextWave { stage: usize },
-    Sound { sound: EventSound },
+    NextWave {
+        stage: usize,
+    },
+    WaveCleared {
+        stage: usize,
+        duration: f32,
+    },
+    Sound {
+        sound: EventSound,
+        position: Vec2,
+        intensity: f32,
+    },
+    EntityDied {
+        entity_kind: EntityKind,
+        cause: DamageSource,
+        position: Vec2,
+    },
+    PlayerDamaged {
+        amount: f32,
+        source: DamageSource,
+    },
+    PlayerDied {
+        stage: usize,
+    },
+    HeadHit {
+        strength: f32,
+        position: Vec2,
+    },
+    AttackFired {
+        attack_kind: AttackKind,
+        position: Vec2,
+    },
+    PickupCollected {
+        pickup: PickupKind,
+        position: Vec2,
+    },
+    BeamFired {
+        start: Vec2,
+        end: Vec2,
+    },
+    MutatorAdded {
+        mutator: Mutator,
+    },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EventSound {
     BodyHit,
     HeadHit,
@@ -10,3 +57,20 @@ pub enum EventSound {
     Bounce,
     Explosion,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageSource {
+    PlayerBody,
+    PlayerHead,
+    Entity(EntityKind),
+    Expired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PickupKind {
+    HealZone,
+}
+
+pub trait EventHandler {
+    fn handle_event(&mut self, event: &Event);
+}
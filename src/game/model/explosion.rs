@@ -1,61 +1,13 @@
 use super::*;
 
-pub struct Explosion {
-    entity: Entity,
-    entity_type: EntityType,
-    radius: f32,
-    speed: f32,
-    hit_strength: f32,
-}
-
-impl Explosion {
-    pub fn new(position: Vec2, explosion_info: ExplosionInfo) -> Self {
-        Self {
-            entity: Entity::new(position, explosion_info.entity_info),
-            entity_type: explosion_info.entity_type,
-            radius: explosion_info.radius,
-            speed: explosion_info.speed,
-            hit_strength: explosion_info.hit_strength,
-        }
-    }
-}
-
-impl EntityObject for Explosion {
-    fn entity_mut(&mut self) -> &mut Entity {
-        &mut self.entity
-    }
-
-    fn entity(&self) -> &Entity {
-        &self.entity
-    }
-
-    fn entity_type(&self) -> EntityType {
-        self.entity_type
-    }
-
-    fn attack(&mut self, _: Option<Vec2>, delta_time: f32, _: &mut Commands) {
-        self.entity.rigidbody.collider.radius += delta_time * self.speed;
-        if self.entity.rigidbody.collider.radius > self.radius {
-            self.entity.destroy = true;
-        }
-    }
-
-    fn health_frac(&self) -> f32 {
-        0.0
-    }
-
-    fn hit_strength(&self) -> Option<f32> {
-        Some(self.hit_strength)
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ExplosionInfo {
-    entity_info: EntityInfo,
-    entity_type: EntityType,
-    radius: f32,
-    speed: f32,
-    hit_strength: f32,
+    pub entity_info: EntityInfo,
+    pub entity_type: EntityType,
+    pub radius: f32,
+    pub speed: f32,
+    pub hit_strength: f32,
+    pub shockwave: Option<f32>,
 }
 
 impl ExplosionInfo {
@@ -74,12 +26,59 @@ impl ExplosionInfo {
             radius,
             speed,
             hit_strength,
+            shockwave: None,
+        }
+    }
+
+    pub fn shockwave(
+        entity_type: EntityType,
+        radius: f32,
+        speed: f32,
+        strength: f32,
+        damage: f32,
+    ) -> Self {
+        Self {
+            entity_info: EntityInfo::new(
+                Health::new(0.0),
+                1.0,
+                true,
+                0.0,
+                0.0,
+                SHOCKWAVE_COLOR,
+                PhysicsMaterial::new(0.0, 0.0),
+            ),
+            entity_type,
+            radius,
+            speed,
+            hit_strength: strength,
+            shockwave: Some(damage),
         }
     }
-}
 
-impl EntityObjectInfo for ExplosionInfo {
-    fn into_entity_object(self: Box<Self>, position: Vec2) -> Box<dyn EntityObject> {
-        Box::new(Explosion::new(position, *self))
+    pub fn spawn(self, position: Vec2, store: &mut EntityStore) -> EntityId {
+        let shockwave = self.shockwave.map(|damage| Shockwave {
+            strength: self.hit_strength,
+            damage,
+            hit: vec![],
+        });
+        let id = store.spawn(
+            position,
+            EntityKind::Explosion,
+            self.entity_type,
+            self.entity_info,
+        );
+        store.explosions.insert(
+            id,
+            Explosion {
+                radius: self.radius,
+                speed: self.speed,
+                shockwave,
+            },
+        );
+        store.hit_strengths.insert(id, self.hit_strength);
+        if self.shockwave.is_some() {
+            store.healths.remove(id);
+        }
+        id
     }
 }
@@ -0,0 +1,80 @@
+use super::*;
+
+/// How strongly hits stun enemies and freeze the simulation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ImpactTuning {
+    pub stun_threshold: f32,
+    pub stun_per_strength: f32,
+    pub stun_max: f32,
+    pub hit_stop_threshold: f32,
+    pub hit_stop_per_strength: f32,
+    pub hit_stop_max: f32,
+}
+
+impl Default for ImpactTuning {
+    fn default() -> Self {
+        Self {
+            stun_threshold: 40.0,
+            stun_per_strength: 0.005,
+            stun_max: 1.5,
+            hit_stop_threshold: 150.0,
+            hit_stop_per_strength: 0.0005,
+            hit_stop_max: 0.15,
+        }
+    }
+}
+
+impl ImpactTuning {
+    pub fn stun_time(&self, strength: f32) -> f32 {
+        scaled(
+            strength,
+            self.stun_threshold,
+            self.stun_per_strength,
+            self.stun_max,
+        )
+    }
+
+    pub fn hit_stop_time(&self, strength: f32) -> f32 {
+        scaled(
+            strength,
+            self.hit_stop_threshold,
+            self.hit_stop_per_strength,
+            self.hit_stop_max,
+        )
+    }
+}
+
+fn scaled(strength: f32, threshold: f32, per_strength: f32, max: f32) -> f32 {
+    if strength < threshold {
+        0.0
+    } else {
+        (strength * per_strength).min(max)
+    }
+}
+
+impl Model {
+    /// Stuns the entity and starts a hit-stop if the hit was strong enough
+    pub fn impact(&mut self, id: EntityId, strength: f32) {
+        let stun_time = self.impact_tuning.stun_time(strength);
+        if stun_time > 0.0 {
+            match self.store.stuns.get_mut(id) {
+                Some(stun) => stun.time_left = stun.time_left.max(stun_time),
+                None => {
+                    self.store.stuns.insert(
+                        id,
+                        Stun {
+                            time_left: stun_time,
+                        },
+                    );
+                }
+            }
+        }
+        self.hit_stop = self
+            .hit_stop
+            .max(self.impact_tuning.hit_stop_time(strength));
+    }
+
+    pub fn is_stunned(&self, id: EntityId) -> bool {
+        self.store.stuns.get(id).is_some()
+    }
+}
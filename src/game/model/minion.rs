@@ -1,40 +1,8 @@
 use super::*;
 
-pub struct Minion {
-    pub entity: Entity,
-}
-
-impl Minion {
-    pub fn new(position: Vec2, minion_info: MinionInfo) -> Self {
-        Self {
-            entity: Entity::new(position, minion_info.entity_info),
-        }
-    }
-}
-
-impl EntityObject for Minion {
-    fn entity_mut(&mut self) -> &mut Entity {
-        &mut self.entity
-    }
-
-    fn entity(&self) -> &Entity {
-        &self.entity
-    }
+const MINION_FOLLOW_DISTANCE: f32 = 15.0;
 
-    fn entity_type(&self) -> EntityType {
-        EntityType::Minion
-    }
-
-    fn attack_targets(&self) -> Vec<EntityType> {
-        vec![EntityType::Enemy]
-    }
-
-    fn movement_targets(&self) -> Vec<EntityType> {
-        vec![EntityType::Enemy]
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MinionInfo {
     pub entity_info: EntityInfo,
 }
@@ -43,10 +11,21 @@ impl MinionInfo {
     pub fn new(entity_info: EntityInfo) -> Self {
         Self { entity_info }
     }
-}
 
-impl EntityObjectInfo for MinionInfo {
-    fn into_entity_object(self: Box<Self>, position: Vec2) -> Box<dyn EntityObject> {
-        Box::new(Minion::new(position, *self))
+    pub fn spawn(self, position: Vec2, store: &mut EntityStore) -> EntityId {
+        let id = store.spawn(
+            position,
+            EntityKind::Minion,
+            EntityType::Minion,
+            self.entity_info,
+        );
+        store.ais.insert(
+            id,
+            Ai::Follow {
+                leader: None,
+                distance: MINION_FOLLOW_DISTANCE,
+            },
+        );
+        id
     }
 }
@@ -1,101 +1,96 @@
 use super::*;
 
-pub struct Missile {
-    entity: Entity,
-    entity_type: EntityType,
-    target_pos: Option<Vec2>,
-    target_precision: f32,
-    attack: bool,
-    impact_attack: Attack,
-}
-
-impl Missile {
-    fn new(position: Vec2, missile_info: MissileInfo) -> Self {
-        Self {
-            entity: Entity::new(position, missile_info.entity_info),
-            entity_type: missile_info.entity_type,
-            target_pos: missile_info.target_pos,
-            target_precision: 0.5,
-            attack: false,
-            impact_attack: Attack {
-                attack_time: Health::new(0.0),
-                attack_type: AttackType::Explode {
-                    projectile_count: missile_info.bombs_count,
-                    projectile: missile_info.bomb,
-                },
-            },
-        }
-    }
-}
-
-impl EntityObject for Missile {
-    fn entity_mut(&mut self) -> &mut Entity {
-        &mut self.entity
-    }
+const MISSILE_TARGET_PRECISION: f32 = 0.5;
 
-    fn entity(&self) -> &Entity {
-        &self.entity
-    }
-
-    fn entity_type(&self) -> EntityType {
-        self.entity_type
-    }
-
-    fn attack(&mut self, _: Option<Vec2>, _: f32, commands: &mut Commands) {
-        if self.attack
-            || self
-                .target_pos
-                .map(|target_pos| {
-                    (self.entity.rigidbody.position - target_pos).length() <= self.target_precision
-                })
-                .unwrap_or(false)
-        {
-            self.entity.health.kill();
-            self.impact_attack.perform(&mut self.entity, commands);
-        }
-    }
-
-    fn on_collide_bounds(&mut self, commands: &mut Commands) {
-        commands.event(Event::Sound {
-            sound: EventSound::Bounce,
-        });
-        self.attack = true;
-    }
-
-    fn on_collide(&mut self, _: &mut Commands) {
-        self.attack = true;
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MissileInfo {
-    entity_info: EntityInfo,
-    entity_type: EntityType,
-    target_pos: Option<Vec2>,
-    bombs_count: usize,
-    bomb: Box<dyn EntityObjectInfo>,
+    pub entity_info: EntityInfo,
+    pub entity_type: EntityType,
+    pub target: Option<Target>,
+    pub homing: Option<Homing>,
+    pub fuel: Option<Health>,
+    pub bombs_count: usize,
+    pub bomb: Box<EntityBlueprint>,
 }
 
 impl MissileInfo {
     pub fn new(
         entity_info: EntityInfo,
         entity_type: EntityType,
-        target_pos: Option<Vec2>,
+        target: Option<Target>,
         bombs_count: usize,
-        bomb: Box<dyn EntityObjectInfo>,
+        bomb: EntityBlueprint,
     ) -> Self {
         Self {
             entity_info,
             entity_type,
-            target_pos,
+            target,
+            homing: None,
+            fuel: None,
             bombs_count,
-            bomb,
+            bomb: Box::new(bomb),
         }
     }
-}
 
-impl EntityObjectInfo for MissileInfo {
-    fn into_entity_object(self: Box<Self>, position: Vec2) -> Box<dyn EntityObject> {
-        Box::new(Missile::new(position, *self))
+    pub fn homing(
+        entity_info: EntityInfo,
+        entity_type: EntityType,
+        homing: Homing,
+        fuel: Health,
+        bombs_count: usize,
+        bomb: EntityBlueprint,
+    ) -> Self {
+        Self {
+            entity_info,
+            entity_type,
+            target: None,
+            homing: Some(homing),
+            fuel: Some(fuel),
+            bombs_count,
+            bomb: Box::new(bomb),
+        }
+    }
+
+    pub fn spawn(self, position: Vec2, store: &mut EntityStore) -> EntityId {
+        let id = store.spawn(
+            position,
+            EntityKind::Missile,
+            self.entity_type,
+            self.entity_info,
+        );
+        store.guidances.insert(
+            id,
+            Guidance {
+                target: self.target,
+                precision: MISSILE_TARGET_PRECISION,
+                homing: self.homing,
+            },
+        );
+        if let Some(fuel) = self.fuel {
+            store.lifetimes.insert(
+                id,
+                Lifetime {
+                    time: fuel,
+                    on_expire: Expire::Trigger,
+                    expire_on_bounds: false,
+                },
+            );
+        }
+        store.impacts.insert(
+            id,
+            Impact {
+                attack: Attack {
+                    attack_time: Health::new(0.0),
+                    telegraph: None,
+                    attack_type: AttackType::Explode {
+                        projectile_count: self.bombs_count,
+                        projectile: self.bomb,
+                    },
+                },
+                triggered: false,
+                trigger_on_bounds: true,
+            },
+        );
+        id
     }
 }
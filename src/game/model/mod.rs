@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 use super::*;
@@ -5,20 +6,28 @@ use super::*;
 mod area_effect;
 mod attack;
 mod bomb;
+mod cheats;
 mod commands;
 mod corpse;
+mod difficulty;
 mod enemy;
 mod entity;
 mod event;
 mod explosion;
 mod health;
+mod impact;
 mod minion;
 mod missile;
+mod mutator;
 mod particle;
 mod physics;
 mod player;
 mod projectile;
+mod rng;
+mod save;
 mod spawner;
+mod stats;
+mod store;
 mod update;
 mod wave;
 
@@ -26,80 +35,155 @@ pub use area_effect::*;
 pub use attack::*;
 use bomb::*;
 use commands::*;
-use corpse::*;
+pub use difficulty::*;
 pub use enemy::*;
 pub use entity::*;
 pub use event::*;
 pub use explosion::*;
 pub use health::*;
+pub use impact::*;
 pub use minion::*;
 use missile::*;
+pub use mutator::*;
 pub use particle::*;
 pub use physics::*;
 pub use player::*;
 use projectile::*;
+pub use rng::*;
+pub use save::*;
 use spawner::*;
+pub use stats::*;
+pub use store::*;
 use wave::*;
 
 const PLAYER_SPEED: f32 = 50.0;
 const HEAD_SPEED: f32 = 150.0;
 const BODY_HIT_STRENGTH: f32 = 150.0;
+const EXPLOSION_INTENSITY: f32 = 500.0;
+/// Explosions left behind by enemies with the explosive deaths mutator
+const DEATH_EXPLOSION_RADIUS: f32 = 8.0;
 const DRAG: f32 = 1.0;
 const BOUNCINESS: f32 = 0.2;
 const CORPSE_LIFETIME: f32 = 2.5;
 const PARTICLE_LIFETIME: f32 = 1.0;
 const BODY_ACCELERATION: f32 = 3.0;
 const HEAD_ACCELERATION: f32 = 10.0;
+const ARENA_SIZE: Vec2 = Vec2::new(640.0, 360.0);
+/// Half of what the game camera shows on a 4:3 screen, used until the game reports the real view
+const VIEW_HALF_SIZE: Vec2 = Vec2::new(185.0, 140.0);
+/// Keeps spawn groups from overlapping the walls
+const SPAWN_MARGIN: f32 = 15.0;
 
+#[derive(Serialize, Deserialize)]
 pub struct Model {
     pub bounds: Bounds,
     pub spawn_bounds: Bounds,
     pub player: Player,
-    pub enemies: Vec<Box<dyn EntityObject>>,
-    pub minions: Vec<Box<dyn EntityObject>>,
+    pub store: EntityStore,
     pub particles: Vec<Particle>,
+    #[serde(default)]
+    pub emitters: Vec<ParticleEmitter>,
     pub area_effects: Vec<AreaEffect>,
     pub spawners: Vec<Spawner>,
     pub current_stage: usize,
+    pub wave_time: f32,
+    pub stats: RunStats,
+    pub rng: Rng,
+    #[serde(default)]
+    pub impact_tuning: ImpactTuning,
+    /// Simulation time left frozen after a big hit
+    #[serde(default)]
+    pub hit_stop: f32,
+    #[serde(default)]
+    pub god_mode: bool,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub dynamic_difficulty: DynamicDifficulty,
+    #[serde(default)]
+    pub mutators: Vec<Mutator>,
+    /// Adds a random mutator every few stages
+    #[serde(default)]
+    pub roll_mutators: bool,
+    /// What the game camera shows, enemies spawn outside of it
+    #[serde(skip)]
+    pub view: Option<Bounds>,
+    #[serde(skip)]
     pub events: Vec<Event>,
 }
 
 impl Model {
-    pub fn new() -> Self {
+    pub fn new(seed: u64) -> Self {
+        Self::with_arena(seed, ARENA_SIZE)
+    }
+
+    pub fn with_arena(seed: u64, arena_size: Vec2) -> Self {
         let bounds = Bounds {
-            min: vec2(-160.0, -90.0),
-            max: vec2(160.0, 90.0),
+            min: -arena_size / 2.0,
+            max: arena_size / 2.0,
         };
-        Self {
-            bounds,
-            spawn_bounds: Bounds::inside(bounds, 20.0),
-            player: Player::new(
-                vec2(0.0, 0.0),
-                PlayerInfo::new(
-                    10.0,
-                    3.0,
-                    20.0,
-                    EntityInfo::new(
-                        Health::new(250.0),
-                        5.0,
-                        false,
-                        2.0,
-                        PLAYER_SPEED,
-                        BLUE,
-                        PhysicsMaterial::new(0.0, 1.0),
-                    ),
+        let mut store = EntityStore::default();
+        let player = Player::new(
+            vec2(0.0, 0.0),
+            PlayerInfo::new(
+                10.0,
+                3.0,
+                20.0,
+                EntityInfo::new(
+                    Health::new(250.0),
+                    5.0,
+                    false,
+                    2.0,
+                    PLAYER_SPEED,
+                    BLUE,
+                    PhysicsMaterial::new(0.0, 1.0),
                 ),
             ),
-            enemies: vec![],
-            minions: vec![],
+            &mut store,
+        );
+        Self {
+            bounds,
+            spawn_bounds: Bounds::inside(bounds, SPAWN_MARGIN),
+            player,
+            store,
             area_effects: vec![],
             spawners: vec![],
             particles: vec![],
+            emitters: vec![],
             current_stage: 0,
+            wave_time: 0.0,
+            stats: RunStats::default(),
+            rng: Rng::new(seed),
+            impact_tuning: ImpactTuning::default(),
+            hit_stop: 0.0,
+            god_mode: false,
+            difficulty: Difficulty::Normal,
+            dynamic_difficulty: DynamicDifficulty::default(),
+            mutators: vec![],
+            roll_mutators: false,
+            view: None,
             events: vec![],
         }
     }
 
+    pub fn player_body(&self) -> &RigidBody {
+        self.store
+            .rigidbodies
+            .get(self.player.id)
+            .expect("the player is never despawned")
+    }
+
+    pub fn player_health(&self) -> &Health {
+        self.store
+            .healths
+            .get(self.player.id)
+            .expect("the player is never despawned")
+    }
+
+    pub fn player_alive(&self) -> bool {
+        self.player_health().is_alive()
+    }
+
     pub fn move_direction(&mut self, direction: Vec2) {
         let direction = direction.clamp_length_max(1.0);
         self.player.target_body_velocity = direction * PLAYER_SPEED;
@@ -114,13 +198,17 @@ impl Model {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Bounds {
     pub min: Vec2,
     pub max: Vec2,
 }
 
 impl Bounds {
+    pub fn contains(&self, position: Vec2) -> bool {
+        position.cmpge(self.min).all() && position.cmple(self.max).all()
+    }
+
     pub fn inside(bounds: Self, offset: f32) -> Self {
         Self {
             min: bounds.min + vec2(offset, offset),
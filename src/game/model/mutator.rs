@@ -0,0 +1,128 @@
+use super::*;
+
+/// With rolling enabled a new mutator is added every this many stages
+const MUTATOR_ROLL_INTERVAL: usize = 5;
+const LOW_DRAG_SCALE: f32 = 0.25;
+const FAST_SPAWN_SCALE: f32 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Mutator {
+    /// Every enemy leaves an enemy explosion behind, so killing them up close hurts the player
+    ExplosiveDeaths,
+    LowDrag,
+    /// Half health, double body hit strength
+    GlassCannon,
+    /// Nothing loses speed when bouncing off the walls
+    BouncyArena,
+    NoHealZones,
+    FastSpawners,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 6] = [
+        Mutator::ExplosiveDeaths,
+        Mutator::LowDrag,
+        Mutator::GlassCannon,
+        Mutator::BouncyArena,
+        Mutator::NoHealZones,
+        Mutator::FastSpawners,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mutator::ExplosiveDeaths => "EXPLOSIVE DEATHS",
+            Mutator::LowDrag => "LOW DRAG",
+            Mutator::GlassCannon => "GLASS CANNON",
+            Mutator::BouncyArena => "BOUNCY ARENA",
+            Mutator::NoHealZones => "NO HEAL ZONES",
+            Mutator::FastSpawners => "FAST SPAWNERS",
+        }
+    }
+}
+
+impl Model {
+    pub fn has_mutator(&self, mutator: Mutator) -> bool {
+        self.mutators.contains(&mutator)
+    }
+
+    /// Adds a mutator outside of a tick, e.g. the ones picked before a run
+    pub fn add_mutator(&mut self, mutator: Mutator) {
+        let mut commands = Commands::new(self.rng.fork());
+        commands.add_mutator(mutator);
+        self.perform_commands(commands);
+    }
+
+    pub fn apply_mutator(&mut self, mutator: Mutator, events: &mut Vec<Event>) {
+        if self.has_mutator(mutator) {
+            return;
+        }
+        self.mutators.push(mutator);
+        self.stats.mutators.push(mutator.name().to_owned());
+        if let Mutator::GlassCannon = mutator {
+            let player_id = self.player.id;
+            if let Some(health) = self.store.healths.get_mut(player_id) {
+                health.hp_max /= 2.0;
+                health.hp = health.hp.min(health.hp_max);
+            }
+            if let Some(strength) = self.store.hit_strengths.get_mut(player_id) {
+                *strength *= 2.0;
+            }
+        }
+        if let Mutator::LowDrag = mutator {
+            for (_, rigidbody) in self.store.rigidbodies.iter_mut() {
+                rigidbody.physics_material.drag *= LOW_DRAG_SCALE;
+            }
+        }
+        events.push(Event::MutatorAdded { mutator });
+    }
+
+    /// Adds a random inactive mutator every few stages
+    pub fn roll_mutator(&mut self, commands: &mut Commands) {
+        if !self.roll_mutators || self.current_stage % MUTATOR_ROLL_INTERVAL != 0 {
+            return;
+        }
+        let inactive: Vec<Mutator> = Mutator::ALL
+            .iter()
+            .copied()
+            .filter(|&mutator| !self.has_mutator(mutator))
+            .collect();
+        if !inactive.is_empty() {
+            let mutator = inactive[self.rng.gen_range(0, inactive.len())];
+            commands.add_mutator(mutator);
+        }
+    }
+
+    /// Spawns a blueprint with the drag of the active mutators
+    pub fn spawn_blueprint(&mut self, blueprint: EntityBlueprint, position: Vec2) -> EntityId {
+        let drag_scale = self.drag_scale();
+        let id = blueprint.spawn(position, &mut self.store);
+        if let Some(rigidbody) = self.store.rigidbodies.get_mut(id) {
+            rigidbody.physics_material.drag *= drag_scale;
+        }
+        id
+    }
+
+    pub fn drag_scale(&self) -> f32 {
+        if self.has_mutator(Mutator::LowDrag) {
+            LOW_DRAG_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    pub fn bounds_bounciness(&self) -> Option<f32> {
+        if self.has_mutator(Mutator::BouncyArena) {
+            Some(1.0)
+        } else {
+            None
+        }
+    }
+
+    pub fn spawn_time_scale(&self) -> f32 {
+        if self.has_mutator(Mutator::FastSpawners) {
+            FAST_SPAWN_SCALE
+        } else {
+            1.0
+        }
+    }
+}
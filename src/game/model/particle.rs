@@ -1,36 +1,262 @@
 use super::*;
 
+#[derive(Serialize, Deserialize)]
 pub struct Particle {
     pub rigidbody: RigidBody,
+    #[serde(with = "ColorDef")]
     pub color: Color,
     pub lifetime: Health,
+    pub style: ParticleStyle,
+}
+
+/// How a particle changes over its life
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ParticleStyle {
+    pub size: (f32, f32),
+    #[serde(with = "ColorDef")]
+    pub color_start: Color,
+    #[serde(with = "ColorDef")]
+    pub color_end: Color,
+    pub gravity: Vec2,
+    pub collide_bounds: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParticlePreset {
+    HitSparks,
+    Explosion,
+    HealSparkles,
+    SpawnerPortal,
+    DeathBurst,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EmitterShape {
+    Point,
+    /// Anywhere inside the emitter radius, moving outward
+    Circle,
+    /// On the edge of the emitter radius, moving outward
+    Ring,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Emission {
+    /// Emits once, the count grows with the intensity
+    Burst {
+        per_intensity: f32,
+        min: usize,
+        max: usize,
+    },
+    /// Emits continuously for the emitter's duration
+    Rate { per_second: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EmitterDefinition {
+    pub shape: EmitterShape,
+    pub emission: Emission,
+    /// Negative speeds move particles toward the center
+    pub speed: (f32, f32),
+    pub lifetime: (f32, f32),
+    pub drag: f32,
+    pub style: ParticleStyle,
+}
+
+impl ParticlePreset {
+    pub fn definition(self, color: Color) -> EmitterDefinition {
+        let fade = |alpha: f32| Color::new(color.r, color.g, color.b, alpha);
+        match self {
+            ParticlePreset::HitSparks => EmitterDefinition {
+                shape: EmitterShape::Point,
+                emission: Emission::Burst {
+                    per_intensity: 0.1,
+                    min: 1,
+                    max: 50,
+                },
+                speed: (10.0, 30.0),
+                lifetime: (PARTICLE_LIFETIME, PARTICLE_LIFETIME),
+                drag: DRAG,
+                style: ParticleStyle {
+                    size: (1.0, 1.0),
+                    color_start: fade(0.5),
+                    color_end: fade(0.0),
+                    gravity: Vec2::ZERO,
+                    collide_bounds: true,
+                },
+            },
+            ParticlePreset::Explosion => EmitterDefinition {
+                shape: EmitterShape::Circle,
+                emission: Emission::Burst {
+                    per_intensity: 0.1,
+                    min: 20,
+                    max: 60,
+                },
+                speed: (20.0, 60.0),
+                lifetime: (0.4, 0.9),
+                drag: 3.0,
+                style: ParticleStyle {
+                    size: (2.0, 0.5),
+                    color_start: fade(0.9),
+                    color_end: Color::new(0.3, 0.3, 0.3, 0.0),
+                    gravity: Vec2::ZERO,
+                    collide_bounds: true,
+                },
+            },
+            ParticlePreset::HealSparkles => EmitterDefinition {
+                shape: EmitterShape::Circle,
+                emission: Emission::Rate { per_second: 6.0 },
+                speed: (1.0, 4.0),
+                lifetime: (0.8, 1.5),
+                drag: 0.5,
+                style: ParticleStyle {
+                    size: (0.8, 0.0),
+                    color_start: fade(0.8),
+                    color_end: fade(0.0),
+                    gravity: vec2(0.0, -6.0),
+                    collide_bounds: false,
+                },
+            },
+            ParticlePreset::SpawnerPortal => EmitterDefinition {
+                shape: EmitterShape::Ring,
+                emission: Emission::Rate { per_second: 20.0 },
+                speed: (-12.0, -6.0),
+                lifetime: (0.5, 0.8),
+                drag: 0.0,
+                style: ParticleStyle {
+                    size: (1.0, 0.3),
+                    color_start: fade(0.7),
+                    color_end: fade(0.0),
+                    gravity: Vec2::ZERO,
+                    collide_bounds: false,
+                },
+            },
+            ParticlePreset::DeathBurst => EmitterDefinition {
+                shape: EmitterShape::Point,
+                emission: Emission::Burst {
+                    per_intensity: 0.0,
+                    min: 10,
+                    max: 16,
+                },
+                speed: (5.0, 25.0),
+                lifetime: (0.6, 1.2),
+                drag: 2.0,
+                style: ParticleStyle {
+                    size: (1.5, 0.0),
+                    color_start: fade(0.8),
+                    color_end: fade(0.0),
+                    gravity: Vec2::ZERO,
+                    collide_bounds: true,
+                },
+            },
+        }
+    }
+}
+
+/// A placed preset, rate emitters keep emitting until their duration runs out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleEmitter {
+    pub preset: ParticlePreset,
+    pub position: Vec2,
+    pub radius: f32,
+    pub intensity: f32,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+    pub duration: f32,
+    /// Fractional particles carried over between frames
+    pub accumulated: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(preset: ParticlePreset, position: Vec2, intensity: f32, color: Color) -> Self {
+        Self {
+            preset,
+            position,
+            radius: 0.0,
+            intensity,
+            color,
+            duration: 0.0,
+            accumulated: 0.0,
+        }
+    }
+
+    pub fn area(mut self, radius: f32, duration: f32) -> Self {
+        self.radius = radius;
+        self.duration = duration;
+        self
+    }
 }
 
 impl Model {
-    pub fn spawn_particles_hit(&mut self, position: Vec2, intensity: f32, color: Color) {
-        let particles_count = macroquad::rand::gen_range(1, (intensity / 10.0).min(50.0) as usize);
-        for _ in 0..particles_count {
-            let direction = Self::get_random_direction();
-            let velocity = macroquad::rand::gen_range(10.0, 30.0);
-            let velocity = direction * velocity;
-            self.particles.push(Particle {
-                rigidbody: RigidBody {
-                    position,
-                    velocity,
-                    mass: 1.0,
-                    is_kinematic: false,
-                    collider: Collider::new(1.0),
-                    physics_material: PhysicsMaterial::new(DRAG, BOUNCINESS),
-                },
-                color,
-                lifetime: Health::new(PARTICLE_LIFETIME),
-            })
+    /// Bursts emit right away, rate emitters are kept until they run out
+    pub fn emit_particles(&mut self, emitter: ParticleEmitter) {
+        let definition = emitter.preset.definition(emitter.color);
+        match definition.emission {
+            Emission::Burst {
+                per_intensity,
+                min,
+                max,
+            } => {
+                let max = ((emitter.intensity * per_intensity) as usize).clamp(min, max);
+                let count = self.rng.gen_range(min, max);
+                for _ in 0..count {
+                    self.emit_particle(&emitter, &definition);
+                }
+            }
+            Emission::Rate { .. } => self.emitters.push(emitter),
         }
     }
 
-    pub fn get_random_direction() -> Vec2 {
-        let angle = macroquad::rand::gen_range(0.0, std::f32::consts::PI * 2.0);
-        let (sin, cos) = angle.sin_cos();
-        vec2(cos, sin)
+    fn emit_particle(&mut self, emitter: &ParticleEmitter, definition: &EmitterDefinition) {
+        let direction = self.rng.direction();
+        let offset = match definition.shape {
+            EmitterShape::Point => 0.0,
+            EmitterShape::Circle => self.rng.gen_range(0.0, emitter.radius),
+            EmitterShape::Ring => emitter.radius,
+        };
+        let speed = self.rng.gen_range(definition.speed.0, definition.speed.1);
+        let lifetime = self
+            .rng
+            .gen_range(definition.lifetime.0, definition.lifetime.1);
+        let style = definition.style;
+        self.particles.push(Particle {
+            rigidbody: RigidBody {
+                position: emitter.position + direction * offset,
+                velocity: direction * speed,
+                mass: 1.0,
+                is_kinematic: false,
+                collider: Collider::new(style.size.0),
+                physics_material: PhysicsMaterial::new(definition.drag, BOUNCINESS),
+                previous_position: None,
+            },
+            color: style.color_start,
+            lifetime: Health::new(lifetime),
+            style,
+        })
     }
+
+    pub fn update_emitters(&mut self, delta_time: f32) {
+        let mut emitters = std::mem::take(&mut self.emitters);
+        for emitter in &mut emitters {
+            let definition = emitter.preset.definition(emitter.color);
+            if let Emission::Rate { per_second } = definition.emission {
+                emitter.accumulated += per_second * delta_time.min(emitter.duration);
+                while emitter.accumulated >= 1.0 {
+                    emitter.accumulated -= 1.0;
+                    self.emit_particle(emitter, &definition);
+                }
+            }
+            emitter.duration -= delta_time;
+        }
+        emitters.retain(|emitter| emitter.duration > 0.0);
+        self.emitters = emitters;
+    }
+}
+
+pub fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    Color::new(
+        from.r + (to.r - from.r) * t,
+        from.g + (to.g - from.g) * t,
+        from.b + (to.b - from.b) * t,
+        from.a + (to.a - from.a) * t,
+    )
 }
@@ -1,6 +1,6 @@
 use super::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RigidBody {
     pub position: Vec2,
     pub velocity: Vec2,
@@ -8,6 +8,9 @@ pub struct RigidBody {
     pub is_kinematic: bool,
     pub collider: Collider,
     pub physics_material: PhysicsMaterial,
+    /// Position at the start of the last fixed tick, used to interpolate drawing
+    #[serde(skip)]
+    pub previous_position: Option<Vec2>,
 }
 
 impl RigidBody {
@@ -25,6 +28,18 @@ impl RigidBody {
             is_kinematic,
             collider,
             physics_material,
+            previous_position: None,
+        }
+    }
+
+    pub fn store_position(&mut self) {
+        self.previous_position = Some(self.position);
+    }
+
+    pub fn interpolated_position(&self, alpha: f32) -> Vec2 {
+        match self.previous_position {
+            Some(previous) => previous.lerp(self.position, alpha),
+            None => self.position,
         }
     }
 
@@ -82,17 +97,18 @@ impl RigidBody {
         self.position = self.position.clamp(bounds.min + size, bounds.max - size);
     }
 
-    pub fn bounce_bounds(&mut self, bounds: &Bounds) -> bool {
+    pub fn bounce_bounds(&mut self, bounds: &Bounds, bounciness_override: Option<f32>) -> bool {
+        let bounciness = bounciness_override.unwrap_or(self.physics_material.bounciness);
         let size = vec2(self.collider.radius, self.collider.radius);
         let min = self.position - size;
         let max = self.position + size;
         let mut bounce = false;
         if min.x < bounds.min.x || max.x > bounds.max.x {
-            self.velocity.x *= -self.physics_material.bounciness;
+            self.velocity.x *= -bounciness;
             bounce = true;
         }
         if min.y < bounds.min.y || max.y > bounds.max.y {
-            self.velocity.y *= -self.physics_material.bounciness;
+            self.velocity.y *= -bounciness;
             bounce = true;
         }
         self.clamp_bounds(bounds);
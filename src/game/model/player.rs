@@ -1,7 +1,8 @@
 use super::*;
 
+#[derive(Serialize, Deserialize)]
 pub struct Player {
-    pub entity: Entity,
+    pub id: EntityId,
     pub head: RigidBody,
     pub chain_length: f32,
     pub head_target: Vec2,
@@ -12,8 +13,16 @@ pub struct Player {
 }
 
 impl Player {
-    pub fn new(position: Vec2, player_info: PlayerInfo) -> Self {
+    pub fn new(position: Vec2, player_info: PlayerInfo, store: &mut EntityStore) -> Self {
+        let id = store.spawn(
+            position,
+            EntityKind::Player,
+            EntityType::Player,
+            player_info.entity_info,
+        );
+        store.hit_strengths.insert(id, BODY_HIT_STRENGTH);
         Self {
+            id,
             head: RigidBody::new(
                 position + vec2(player_info.chain_length, 0.0),
                 player_info.head_mass,
@@ -21,7 +30,6 @@ impl Player {
                 Collider::new(player_info.head_size),
                 PhysicsMaterial::new(0.0, 0.0),
             ),
-            entity: Entity::new(position, player_info.entity_info),
             chain_length: player_info.chain_length,
             head_target: vec2(1.0, 0.0),
             target_body_velocity: vec2(0.0, 0.0),
@@ -32,7 +40,7 @@ impl Player {
             //     attack_time: Health::new(1.0),
             //     attack_type: AttackType::Shoot {
             //         target_pos: vec2(0.0, 0.0),
-            //         projectile: Box::new(MissileInfo::new(
+            //         projectile: Box::new(EntityBlueprint::Missile(MissileInfo::new(
             //             EntityInfo::new(
             //                 Health::new(1000.0),
             //                 20.0,
@@ -45,7 +53,7 @@ impl Player {
             //             EntityType::Minion,
             //             None,
             //             6,
-            //             Box::new(BombInfo::new(
+            //             EntityBlueprint::Bomb(BombInfo::new(
             //                 EntityInfo::new(
             //                     Health::new(100.0),
             //                     5.0,
@@ -57,91 +65,17 @@ impl Player {
             //                 ),
             //                 EntityType::Minion,
             //                 Health::new(0.3),
-            //                 Box::new(ExplosionInfo::new(EntityType::Minion, 5.0, 10.0, 100.0)),
+            //                 EntityBlueprint::Explosion(ExplosionInfo::new(EntityType::Minion, 5.0, 10.0, 100.0)),
             //             )),
-            //         )),
+            //         ))),
             //     },
+            //     telegraph: None,
             // }],
         }
     }
 }
 
-impl EntityObject for Player {
-    fn entity_mut(&mut self) -> &mut Entity {
-        &mut self.entity
-    }
-
-    fn entity(&self) -> &Entity {
-        &self.entity
-    }
-
-    fn entity_type(&self) -> EntityType {
-        EntityType::Player
-    }
-
-    fn decide_movement(&mut self, _: Option<Vec2>, delta_time: f32) {
-        if self.entity.is_alive() {
-            // Calculate head target velocity
-            let direction = self.head.position - self.entity.rigidbody.position;
-            let target = self.head_target - self.entity.rigidbody.position;
-            let angle = direction.angle_between(target).abs();
-            let speed = angle.min(0.2) / 0.2;
-            let direction = vec2(direction.y, -direction.x).normalize();
-            let signum = direction.dot(target).signum();
-            let direction = direction * signum * speed;
-            self.target_head_velocity = direction * HEAD_SPEED + self.entity.rigidbody.velocity;
-
-            // Accelerate towards target velocity
-            let target_change = self.target_body_velocity - self.entity.rigidbody.velocity;
-            self.entity.rigidbody.velocity += target_change * BODY_ACCELERATION * delta_time;
-
-            let target_change = self.target_head_velocity - self.head.velocity;
-            self.head.velocity += target_change * HEAD_ACCELERATION * delta_time;
-        }
-    }
-
-    fn movement(&mut self, delta_time: f32) {
-        self.entity.rigidbody.movement(delta_time);
-        self.head.movement(delta_time);
-
-        if self.entity.rigidbody.velocity.length() > self.entity.movement_speed {
-            self.entity.rigidbody.drag(delta_time);
-        }
-
-        // Clamp distance between body and head
-        let offset = self.head.position - self.entity.rigidbody.position;
-        let distance = offset.length() - self.chain_length;
-        self.head.position -= offset.normalize_or_zero() * distance;
-    }
-
-    fn dead(&mut self, _delta_time: f32) -> DeadState {
-        DeadState::Idle
-    }
-
-    fn hit_strength(&self) -> Option<f32> {
-        Some(BODY_HIT_STRENGTH)
-    }
-
-    fn attack(&mut self, _: Option<Vec2>, delta_time: f32, commands: &mut Commands) {
-        for attack in &mut self.attacks {
-            attack.attack_time.change(-delta_time);
-            match &mut attack.attack_type {
-                AttackType::Shoot { target_pos, .. } => {
-                    *target_pos = self.head_target;
-                }
-                _ => (),
-            }
-        }
-
-        let attacks = std::mem::take(&mut self.perform_attacks);
-        for attack_index in attacks {
-            let attack = self.attacks.get_mut(attack_index).unwrap();
-            attack.perform(&mut self.entity, commands);
-        }
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PlayerInfo {
     pub entity_info: EntityInfo,
     pub head_mass: f32,
@@ -159,9 +93,3 @@ impl PlayerInfo {
         }
     }
 }
-
-impl EntityObjectInfo for PlayerInfo {
-    fn into_entity_object(self: Box<Self>, position: Vec2) -> Box<dyn EntityObject> {
-        Box::new(Player::new(position, *self))
-    }
-}
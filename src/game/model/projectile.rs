@@ -1,58 +1,10 @@
 use super::*;
 
-pub struct Projectile {
-    entity: Entity,
-    entity_type: EntityType,
-    lifetime: Health,
-}
-
-impl Projectile {
-    pub fn new(position: Vec2, projectile_info: ProjectileInfo) -> Self {
-        Self {
-            entity: Entity::new(position, projectile_info.entity_info),
-            entity_type: projectile_info.entity_type,
-            lifetime: projectile_info.lifetime,
-        }
-    }
-}
-
-impl EntityObject for Projectile {
-    fn entity_mut(&mut self) -> &mut Entity {
-        &mut self.entity
-    }
-
-    fn entity(&self) -> &Entity {
-        &self.entity
-    }
-
-    fn entity_type(&self) -> EntityType {
-        self.entity_type
-    }
-
-    fn attack(&mut self, _: Option<Vec2>, delta_time: f32, _: &mut Commands) {
-        self.lifetime.change(-delta_time);
-        if !self.lifetime.is_alive() {
-            self.entity.health.kill();
-        }
-    }
-
-    fn health_frac(&self) -> f32 {
-        self.lifetime.hp_frac()
-    }
-
-    fn on_collide_bounds(&mut self, commands: &mut Commands) {
-        commands.event(Event::Sound {
-            sound: EventSound::Bounce,
-        });
-        self.lifetime.kill();
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ProjectileInfo {
-    entity_info: EntityInfo,
-    entity_type: EntityType,
-    lifetime: Health,
+    pub entity_info: EntityInfo,
+    pub entity_type: EntityType,
+    pub lifetime: Health,
 }
 
 impl ProjectileInfo {
@@ -63,10 +15,22 @@ impl ProjectileInfo {
             lifetime,
         }
     }
-}
 
-impl EntityObjectInfo for ProjectileInfo {
-    fn into_entity_object(self: Box<Self>, position: Vec2) -> Box<dyn EntityObject> {
-        Box::new(Projectile::new(position, *self))
+    pub fn spawn(self, position: Vec2, store: &mut EntityStore) -> EntityId {
+        let id = store.spawn(
+            position,
+            EntityKind::Projectile,
+            self.entity_type,
+            self.entity_info,
+        );
+        store.lifetimes.insert(
+            id,
+            Lifetime {
+                time: self.lifetime,
+                on_expire: Expire::Kill,
+                expire_on_bounds: true,
+            },
+        );
+        id
     }
 }
@@ -0,0 +1,73 @@
+use super::*;
+
+const DEFAULT_INC: u64 = 1442695040888963407;
+const MULTIPLIER: u64 = 6364136223846793005;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+pub trait RandomRange {
+    fn from_unit(low: Self, high: Self, unit: f64) -> Self;
+}
+
+macro_rules! impl_random_range {
+    ($($ty:ty),*) => {
+        $(
+            impl RandomRange for $ty {
+                fn from_unit(low: Self, high: Self, unit: f64) -> Self {
+                    (low as f64 + (high as f64 - low as f64) * unit) as Self
+                }
+            }
+        )*
+    };
+}
+
+impl_random_range!(f32, usize);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { state: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn fork(&mut self) -> Self {
+        let seed = (self.next_u32() as u64) << 32 | self.next_u32() as u64;
+        Self::new(seed)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old_state = self.state;
+        self.state = old_state.wrapping_mul(MULTIPLIER).wrapping_add(DEFAULT_INC);
+        let xorshifted = (((old_state >> 18) ^ old_state) >> 27) as u32;
+        let rot = (old_state >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    pub fn gen_range<T: RandomRange>(&mut self, low: T, high: T) -> T {
+        let unit = self.next_u32() as f64 / (u32::MAX as f64 + 1.0);
+        T::from_unit(low, high, unit)
+    }
+
+    pub fn position_bounds(&mut self, bounds: &Bounds) -> Vec2 {
+        let x = self.gen_range(bounds.min.x, bounds.max.x);
+        let y = self.gen_range(bounds.min.y, bounds.max.y);
+        vec2(x, y)
+    }
+
+    pub fn position_circle(&mut self, position: Vec2, radius: f32) -> Vec2 {
+        let direction = self.direction();
+        let distance = self.gen_range(0.0, 1.0);
+        direction * distance * radius + position
+    }
+
+    pub fn direction(&mut self) -> Vec2 {
+        let angle = self.gen_range(0.0, std::f32::consts::PI * 2.0);
+        let (sin, cos) = angle.sin_cos();
+        vec2(cos, sin)
+    }
+}
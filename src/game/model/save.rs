@@ -0,0 +1,79 @@
+use super::*;
+
+const SAVE_PATH: &str = "save.json";
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Color")]
+pub struct ColorDef {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Model {
+    pub fn has_save() -> bool {
+        std::path::Path::new(SAVE_PATH).exists()
+    }
+
+    pub fn save(&self) {
+        match serde_json::to_string(self) {
+            Ok(contents) => {
+                if let Err(error) = std::fs::write(SAVE_PATH, contents) {
+                    log_error!("Failed to save the game: {}", error);
+                }
+            }
+            Err(error) => log_error!("Failed to serialize the game: {}", error),
+        }
+    }
+
+    pub fn load() -> Option<Self> {
+        let contents = std::fs::read_to_string(SAVE_PATH).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(model) => Some(model),
+            Err(error) => {
+                log_warn!("Failed to load the game: {}", error);
+                None
+            }
+        }
+    }
+
+    pub fn delete_save() {
+        if Self::has_save() {
+            if let Err(error) = std::fs::remove_file(SAVE_PATH) {
+                log_warn!("Failed to delete the save: {}", error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(model: &mut Model, frames: usize) {
+        for frame in 0..frames {
+            let angle = frame as f32 * 0.05;
+            model.move_direction(vec2(angle.cos(), angle.sin()));
+            model.head_target(vec2(angle.sin(), angle.cos()) * 50.0);
+            model.fixed_update(1.0 / 60.0);
+        }
+    }
+
+    #[test]
+    fn save_load_step_matches_original() {
+        let mut model = Model::new(1234);
+        step(&mut model, 600);
+
+        let saved = serde_json::to_string(&model).unwrap();
+        let mut loaded: Model = serde_json::from_str(&saved).unwrap();
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), saved);
+
+        step(&mut model, 600);
+        step(&mut loaded, 600);
+        assert_eq!(
+            serde_json::to_string(&loaded).unwrap(),
+            serde_json::to_string(&model).unwrap()
+        );
+    }
+}
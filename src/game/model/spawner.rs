@@ -1,5 +1,6 @@
 use super::*;
 
+#[derive(Serialize, Deserialize)]
 pub struct Spawner {
     pub position: Vec2,
     pub spawn_group: WaveGroup,
@@ -20,24 +21,9 @@ impl Spawner {
 
 impl Model {
     pub fn spawn_group(&mut self, position: Vec2, group: WaveGroup) {
-        for entity_info in group.entities {
-            self.enemies.push(
-                entity_info
-                    .into_entity_object(Self::get_random_position_circle(position, group.radius)),
-            );
+        for blueprint in group.entities {
+            let position = self.rng.position_circle(position, group.radius);
+            self.spawn_blueprint(blueprint, position);
         }
     }
-
-    pub fn get_random_position_bounds(bounds: &Bounds) -> Vec2 {
-        let x = macroquad::rand::gen_range(bounds.min.x, bounds.max.x);
-        let y = macroquad::rand::gen_range(bounds.min.y, bounds.max.y);
-        vec2(x, y)
-    }
-
-    pub fn get_random_position_circle(position: Vec2, radius: f32) -> Vec2 {
-        let angle = macroquad::rand::gen_range(0.0, std::f32::consts::PI * 2.0);
-        let distance = macroquad::rand::gen_range(0.0, 1.0);
-        let (sin, cos) = angle.sin_cos();
-        vec2(cos, sin) * distance * radius + position
-    }
 }
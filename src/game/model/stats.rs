@@ -0,0 +1,114 @@
+use super::*;
+use std::collections::BTreeMap;
+
+const STATS_PATH: &str = "run_stats.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunStats {
+    pub stage: usize,
+    pub kills: BTreeMap<String, usize>,
+    pub head_hits: usize,
+    pub body_hits: usize,
+    pub strongest_hit: f32,
+    pub damage_taken: BTreeMap<String, f32>,
+    pub healing_received: f32,
+    pub wave_times: Vec<f32>,
+    pub distance_traveled: f32,
+    /// Set by console commands, such runs don't unlock achievements
+    #[serde(default)]
+    pub cheated: bool,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub mutators: Vec<String>,
+}
+
+impl RunStats {
+    pub fn total_kills(&self) -> usize {
+        self.kills.values().sum()
+    }
+
+    pub fn total_damage_taken(&self) -> f32 {
+        self.damage_taken.values().sum()
+    }
+
+    pub fn average_wave_time(&self) -> Option<f32> {
+        if self.wave_times.is_empty() {
+            None
+        } else {
+            Some(self.wave_times.iter().sum::<f32>() / self.wave_times.len() as f32)
+        }
+    }
+
+    pub fn record_kill(&mut self, entity_kind: EntityKind) {
+        if let EntityKind::Enemy(enemy_kind) = entity_kind {
+            *self.kills.entry(enemy_kind.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn record_hit(&mut self, strength: f32, head: bool) {
+        if head {
+            self.head_hits += 1;
+        } else {
+            self.body_hits += 1;
+        }
+        self.strongest_hit = self.strongest_hit.max(strength);
+    }
+
+    pub fn record_damage(&mut self, damage: f32, source: DamageSource) {
+        *self.damage_taken.entry(source.to_string()).or_default() += damage;
+    }
+
+    pub fn export(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(contents) => {
+                if let Err(error) = std::fs::write(STATS_PATH, contents) {
+                    log_error!("Failed to export run stats: {}", error);
+                }
+            }
+            Err(error) => log_error!("Failed to serialize run stats: {}", error),
+        }
+    }
+}
+
+impl std::fmt::Display for EnemyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EnemyKind::Melee => "melee",
+            EnemyKind::Ranger => "ranger",
+            EnemyKind::Bomber => "bomber",
+            EnemyKind::Launcher => "launcher",
+            EnemyKind::Sniper => "sniper",
+            EnemyKind::Charger => "charger",
+            EnemyKind::Guardian => "guardian",
+            EnemyKind::Brute => "brute",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::fmt::Display for EntityKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntityKind::Player => write!(f, "player"),
+            EntityKind::Enemy(enemy_kind) => write!(f, "{}", enemy_kind),
+            EntityKind::Minion => write!(f, "minion"),
+            EntityKind::Projectile => write!(f, "projectile"),
+            EntityKind::Bomb => write!(f, "bomb"),
+            EntityKind::Missile => write!(f, "missile"),
+            EntityKind::Explosion => write!(f, "explosion"),
+            EntityKind::Corpse => write!(f, "corpse"),
+        }
+    }
+}
+
+impl std::fmt::Display for DamageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DamageSource::PlayerBody => write!(f, "body"),
+            DamageSource::PlayerHead => write!(f, "head"),
+            DamageSource::Entity(entity_kind) => write!(f, "{}", entity_kind),
+            DamageSource::Expired => write!(f, "expired"),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Appearance {
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Movement {
+    pub speed: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Ai {
+    Chase {
+        targets: Vec<EntityType>,
+    },
+    Follow {
+        leader: Option<EntityId>,
+        distance: f32,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Attacker {
+    pub attack: Attack,
+    pub targets: Vec<EntityType>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lifetime {
+    pub time: Health,
+    pub on_expire: Expire,
+    pub expire_on_bounds: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Expire {
+    Kill,
+    Trigger,
+    Despawn,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Impact {
+    pub attack: Attack,
+    pub triggered: bool,
+    pub trigger_on_bounds: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Target {
+    Position(Vec2),
+    Entity(EntityId),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Guidance {
+    pub target: Option<Target>,
+    pub precision: f32,
+    pub homing: Option<Homing>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Homing {
+    pub targets: Vec<EntityType>,
+    pub turn_rate: f32,
+    pub acceleration: f32,
+    pub max_speed: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Explosion {
+    pub radius: f32,
+    pub speed: f32,
+    pub shockwave: Option<Shockwave>,
+}
+
+/// Pushes opponents away from the center once instead of colliding with them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shockwave {
+    pub strength: f32,
+    pub damage: f32,
+    pub hit: Vec<EntityId>,
+}
+
+/// Body state to restore once the dash is over
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dash {
+    pub time_left: f32,
+    pub mass: f32,
+    pub is_kinematic: bool,
+}
+
+/// Pauses the entity's AI and attacks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stun {
+    pub time_left: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shield {
+    pub direction: Vec2,
+    pub arc: f32,
+    pub radius: f32,
+    pub time_left: f32,
+}
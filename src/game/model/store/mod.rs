@@ -0,0 +1,327 @@
+use super::*;
+
+mod components;
+
+pub use components::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntityId {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Components<T> {
+    entries: Vec<Option<(u32, T)>>,
+}
+
+impl<T> Default for Components<T> {
+    fn default() -> Self {
+        Self { entries: vec![] }
+    }
+}
+
+impl<T> Components<T> {
+    pub fn insert(&mut self, id: EntityId, component: T) {
+        let index = id.index as usize;
+        if self.entries.len() <= index {
+            self.entries.resize_with(index + 1, || None);
+        }
+        self.entries[index] = Some((id.generation, component));
+    }
+
+    pub fn remove(&mut self, id: EntityId) -> Option<T> {
+        let entry = self.entries.get_mut(id.index as usize)?;
+        match entry {
+            Some((generation, _)) if *generation == id.generation => {
+                entry.take().map(|(_, component)| component)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&T> {
+        match self.entries.get(id.index as usize)? {
+            Some((generation, component)) if *generation == id.generation => Some(component),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut T> {
+        match self.entries.get_mut(id.index as usize)? {
+            Some((generation, component)) if *generation == id.generation => Some(component),
+            _ => None,
+        }
+    }
+
+    pub fn get_pair_mut(&mut self, a: EntityId, b: EntityId) -> Option<(&mut T, &mut T)> {
+        if a.index == b.index {
+            return None;
+        }
+        let (low, high, swapped) = if a.index < b.index {
+            (a, b, false)
+        } else {
+            (b, a, true)
+        };
+        let (left, right) = self.entries.split_at_mut(high.index as usize);
+        let low_component = match left.get_mut(low.index as usize)? {
+            Some((generation, component)) if *generation == low.generation => component,
+            _ => return None,
+        };
+        let high_component = match right.get_mut(0)? {
+            Some((generation, component)) if *generation == high.generation => component,
+            _ => return None,
+        };
+        if swapped {
+            Some((high_component, low_component))
+        } else {
+            Some((low_component, high_component))
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                entry.as_ref().map(|(generation, component)| {
+                    (
+                        EntityId {
+                            index: index as u32,
+                            generation: *generation,
+                        },
+                        component,
+                    )
+                })
+            })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut T)> {
+        self.entries
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                entry.as_mut().map(|(generation, component)| {
+                    (
+                        EntityId {
+                            index: index as u32,
+                            generation: *generation,
+                        },
+                        component,
+                    )
+                })
+            })
+    }
+
+    pub fn ids(&self) -> Vec<EntityId> {
+        self.iter().map(|(id, _)| id).collect()
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct EntityStore {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    pub kinds: Components<EntityKind>,
+    pub factions: Components<EntityType>,
+    pub rigidbodies: Components<RigidBody>,
+    pub healths: Components<Health>,
+    pub appearances: Components<Appearance>,
+    pub movements: Components<Movement>,
+    pub ais: Components<Ai>,
+    pub attacks: Components<Attacker>,
+    pub lifetimes: Components<Lifetime>,
+    pub impacts: Components<Impact>,
+    pub guidances: Components<Guidance>,
+    pub explosions: Components<Explosion>,
+    pub dashes: Components<Dash>,
+    pub shields: Components<Shield>,
+    pub stuns: Components<Stun>,
+    pub hit_strengths: Components<f32>,
+    pub damage_sources: Components<DamageSource>,
+}
+
+impl EntityStore {
+    pub fn create(&mut self) -> EntityId {
+        match self.free.pop() {
+            Some(index) => {
+                let generation = &mut self.generations[index as usize];
+                *generation += 1;
+                self.alive[index as usize] = true;
+                EntityId {
+                    index,
+                    generation: *generation,
+                }
+            }
+            None => {
+                let index = self.generations.len() as u32;
+                self.generations.push(0);
+                self.alive.push(true);
+                EntityId {
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    pub fn spawn(
+        &mut self,
+        position: Vec2,
+        entity_kind: EntityKind,
+        faction: EntityType,
+        entity_info: EntityInfo,
+    ) -> EntityId {
+        let id = self.create();
+        self.kinds.insert(id, entity_kind);
+        self.factions.insert(id, faction);
+        self.rigidbodies.insert(
+            id,
+            RigidBody::new(
+                position,
+                entity_info.mass,
+                entity_info.is_kinematic,
+                Collider::new(entity_info.size),
+                entity_info.physics_material,
+            ),
+        );
+        self.healths.insert(id, entity_info.health);
+        self.appearances.insert(
+            id,
+            Appearance {
+                color: entity_info.color,
+            },
+        );
+        self.movements.insert(
+            id,
+            Movement {
+                speed: entity_info.movement_speed,
+            },
+        );
+        id
+    }
+
+    pub fn is_valid(&self, id: EntityId) -> bool {
+        let index = id.index as usize;
+        index < self.generations.len()
+            && self.alive[index]
+            && self.generations[index] == id.generation
+    }
+
+    pub fn despawn(&mut self, id: EntityId) {
+        if !self.is_valid(id) {
+            return;
+        }
+        self.alive[id.index as usize] = false;
+        self.free.push(id.index);
+        self.kinds.remove(id);
+        self.factions.remove(id);
+        self.rigidbodies.remove(id);
+        self.healths.remove(id);
+        self.appearances.remove(id);
+        self.movements.remove(id);
+        self.ais.remove(id);
+        self.attacks.remove(id);
+        self.lifetimes.remove(id);
+        self.impacts.remove(id);
+        self.guidances.remove(id);
+        self.explosions.remove(id);
+        self.dashes.remove(id);
+        self.shields.remove(id);
+        self.stuns.remove(id);
+        self.hit_strengths.remove(id);
+        self.damage_sources.remove(id);
+    }
+
+    pub fn is_alive(&self, id: EntityId) -> bool {
+        self.healths.get(id).is_some_and(|health| health.is_alive())
+    }
+
+    pub fn damage(&mut self, id: EntityId, damage: f32, source: DamageSource) {
+        if let Some(health) = self.healths.get_mut(id) {
+            health.change(-damage);
+            self.damage_sources.insert(id, source);
+        }
+    }
+
+    pub fn kill(&mut self, id: EntityId, source: DamageSource) {
+        if let Some(health) = self.healths.get_mut(id) {
+            health.kill();
+            self.damage_sources.insert(id, source);
+        }
+    }
+
+    pub fn health_frac(&self, id: EntityId) -> f32 {
+        match self.kinds.get(id) {
+            Some(EntityKind::Projectile) => self
+                .lifetimes
+                .get(id)
+                .map_or(0.0, |lifetime| lifetime.time.hp_frac()),
+            Some(EntityKind::Explosion) | Some(EntityKind::Corpse) => 0.0,
+            _ => self.healths.get(id).map_or(0.0, |health| health.hp_frac()),
+        }
+    }
+
+    pub fn hit_strength(&self, id: EntityId) -> Option<f32> {
+        self.hit_strengths.get(id).copied()
+    }
+
+    pub fn entity_info(&self, id: EntityId) -> Option<EntityInfo> {
+        let rigidbody = self.rigidbodies.get(id)?;
+        Some(EntityInfo::new(
+            self.healths
+                .get(id)
+                .cloned()
+                .unwrap_or_else(|| Health::new(0.0)),
+            rigidbody.mass,
+            rigidbody.is_kinematic,
+            rigidbody.collider.radius,
+            self.movements
+                .get(id)
+                .map_or(0.0, |movement| movement.speed),
+            self.appearances
+                .get(id)
+                .map_or(WHITE, |appearance| appearance.color),
+            rigidbody.physics_material,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn(store: &mut EntityStore) -> EntityId {
+        store.spawn(
+            Vec2::ZERO,
+            EntityKind::Corpse,
+            EntityType::Enemy,
+            EntityInfo::new(
+                Health::new(10.0),
+                1.0,
+                false,
+                1.0,
+                0.0,
+                WHITE,
+                PhysicsMaterial::new(0.0, 1.0),
+            ),
+        )
+    }
+
+    #[test]
+    fn stale_ids_miss_reused_slots() {
+        let mut store = EntityStore::default();
+        let stale = spawn(&mut store);
+        store.despawn(stale);
+        let fresh = spawn(&mut store);
+        assert_eq!(stale.index, fresh.index);
+
+        assert!(!store.is_valid(stale));
+        assert!(store.rigidbodies.get(stale).is_none());
+        store.kill(stale, DamageSource::Expired);
+        store.despawn(stale);
+        assert!(store.is_alive(fresh));
+        assert_eq!(store.kinds.ids(), vec![fresh]);
+    }
+}
@@ -1,16 +1,24 @@
-use std::collections::HashMap;
-
 use super::*;
 
 impl Model {
-    pub fn update(&mut self, delta_time: f32) {
+    fn waves(&mut self, delta_time: f32, commands: &mut Commands) {
+        self.wave_time += delta_time;
         let wave_active = self.wave();
         if !wave_active {
-            self.next_wave();
+            if self.current_stage > 0 {
+                self.dynamic_difficulty.wave_cleared(
+                    self.player_health().hp_frac(),
+                    self.stats.total_damage_taken(),
+                    self.wave_time,
+                );
+                commands.event(Event::WaveCleared {
+                    stage: self.current_stage,
+                    duration: self.wave_time,
+                });
+            }
+            self.next_wave(commands);
         }
         self.update_spawners(delta_time);
-
-        self.particles(delta_time);
     }
 
     fn update_spawners(&mut self, delta_time: f32) {
@@ -29,84 +37,386 @@ impl Model {
     }
 
     fn particles(&mut self, delta_time: f32) {
+        self.update_emitters(delta_time);
         for particle in &mut self.particles {
+            let style = &particle.style;
+            particle.rigidbody.velocity += style.gravity * delta_time;
             particle.rigidbody.movement(delta_time);
-            particle.rigidbody.bounce_bounds(&self.bounds);
+            if style.collide_bounds {
+                particle.rigidbody.bounce_bounds(&self.bounds, None);
+            }
             particle.rigidbody.drag(delta_time);
             particle.lifetime.change(-delta_time);
-            particle.color.a = particle.lifetime.hp_frac() * 0.5;
+            let t = 1.0 - particle.lifetime.hp_frac();
+            particle.rigidbody.collider.radius = style.size.0 + (style.size.1 - style.size.0) * t;
+            particle.color = lerp_color(style.color_start, style.color_end, t);
         }
         self.particles
             .retain(|particle| particle.lifetime.is_alive());
     }
 
     pub fn fixed_update(&mut self, delta_time: f32) {
-        let mut commands = Commands::new();
+        self.store_positions();
+
+        // Hit-stop is counted in fixed steps, so it stays deterministic
+        if self.hit_stop > 0.0 {
+            self.hit_stop -= delta_time;
+            return;
+        }
+
+        let mut commands = Commands::new(self.rng.fork());
+        profiler::measure("waves", || self.waves(delta_time, &mut commands));
+        self.simulate(delta_time, commands);
+    }
+
+    /// Runs physics and combat without advancing waves, used behind the menus
+    pub fn idle_update(&mut self, delta_time: f32) {
+        self.store_positions();
+        let commands = Commands::new(self.rng.fork());
+        self.simulate(delta_time, commands);
+    }
 
-        self.attack(delta_time, &mut commands);
-        self.area_effects(delta_time);
-        self.decide_movement(delta_time);
-        self.move_entities(delta_time);
-        self.collide(&mut commands);
-        self.check_dead(delta_time, &mut commands);
+    fn simulate(&mut self, delta_time: f32, mut commands: Commands) {
+        profiler::measure("particles", || self.particles(delta_time));
+        profiler::measure("attack", || self.attack(delta_time, &mut commands));
+        profiler::measure("area_effects", || {
+            self.area_effects(delta_time, &mut commands)
+        });
+        profiler::measure("decide_movement", || self.decide_movement(delta_time));
+        profiler::measure("move_entities", || self.move_entities(delta_time));
+        profiler::measure("collide", || self.collide(&mut commands));
+        profiler::measure("check_dead", || self.check_dead(&mut commands));
 
-        self.perform_commands(commands);
+        profiler::measure("perform_commands", || self.perform_commands(commands));
+    }
+
+    /// Snapshots positions so the renderer can interpolate between ticks
+    fn store_positions(&mut self) {
+        for (_, rigidbody) in self.store.rigidbodies.iter_mut() {
+            rigidbody.store_position();
+        }
+        self.player.head.store_position();
+        for particle in &mut self.particles {
+            particle.rigidbody.store_position();
+        }
     }
 
     fn wave(&mut self) -> bool {
-        !self.player.entity.is_alive()
+        !self.player_alive()
             || self.spawners.len() > 0
-            || self.entities_type(vec![EntityType::Enemy]).any(|_| true)
+            || self
+                .store
+                .factions
+                .iter()
+                .any(|(_, &faction)| faction == EntityType::Enemy)
     }
 
     fn attack(&mut self, delta_time: f32, commands: &mut Commands) {
-        let mut targets = HashMap::new();
-        for (index, entity) in self.entities().enumerate() {
-            let target_types = entity.attack_targets();
-            let entity_pos = entity.rigidbody.position;
-            if let Some(target_pos) = self.find_closest(entity_pos, target_types) {
-                targets.insert(index, target_pos);
+        self.player_attacks(delta_time, commands);
+        self.lifetimes(delta_time, commands);
+        self.attackers(delta_time, commands);
+        self.guidance();
+        self.impacts(commands);
+        self.explosions(delta_time, commands);
+        self.shockwaves(commands);
+        self.dashes(delta_time);
+        self.shields(delta_time);
+        self.stuns(delta_time);
+    }
+
+    fn player_attacks(&mut self, delta_time: f32, commands: &mut Commands) {
+        let position = self.player_body().position;
+        for attack in &mut self.player.attacks {
+            attack.tick(delta_time);
+            attack.attack_type.set_target(self.player.head_target);
+        }
+
+        let attacks = std::mem::take(&mut self.player.perform_attacks);
+        for attack_index in attacks {
+            if let Some(attack) = self.player.attacks.get_mut(attack_index) {
+                attack.perform(self.player.id, position, commands);
             }
         }
-        self.player.attack(None, delta_time, commands);
-        for (index, entity) in self.entities_mut().enumerate() {
-            entity.attack(targets.get(&index).copied(), delta_time, commands);
+    }
+
+    fn lifetimes(&mut self, delta_time: f32, commands: &mut Commands) {
+        let store = &mut self.store;
+        let mut expired = Vec::new();
+        for (id, lifetime) in store.lifetimes.iter_mut() {
+            lifetime.time.change(-delta_time);
+            if lifetime.on_expire == Expire::Despawn {
+                if let Some(appearance) = store.appearances.get_mut(id) {
+                    appearance.color.a = lifetime.time.hp_frac() * 0.5;
+                }
+            }
+            if !lifetime.time.is_alive() {
+                match lifetime.on_expire {
+                    Expire::Kill => expired.push(id),
+                    Expire::Trigger => {
+                        if let Some(impact) = store.impacts.get_mut(id) {
+                            impact.triggered = true;
+                        }
+                    }
+                    Expire::Despawn => commands.despawn(id),
+                }
+            }
+        }
+        for id in expired {
+            store.kill(id, DamageSource::Expired);
         }
     }
 
-    fn find_closest(&self, origin: Vec2, target_types: Vec<EntityType>) -> Option<Vec2> {
-        self.entities_type(target_types)
-            .map(|entity| entity.rigidbody.position)
-            .min_by(|&pos_a, &pos_b| {
-                let dist_a = (pos_a - origin).length();
-                let dist_b = (pos_b - origin).length();
+    fn attackers(&mut self, delta_time: f32, commands: &mut Commands) {
+        for id in self.store.attacks.ids() {
+            if self.is_stunned(id) {
+                continue;
+            }
+            let position = match self.store.rigidbodies.get(id) {
+                Some(rigidbody) => rigidbody.position,
+                None => continue,
+            };
+            let target = self
+                .store
+                .attacks
+                .get(id)
+                .and_then(|attacker| self.find_closest(position, &attacker.targets));
+            let attacker = self.store.attacks.get_mut(id).unwrap();
+            let attack = &mut attacker.attack;
+            attack.tick(delta_time);
+            if let Some((_, target)) = target {
+                attack.attack_type.set_target(target);
+                attack.perform(id, position, commands);
+            }
+            if let Some(color) = attack.charge_color() {
+                if let Some(appearance) = self.store.appearances.get_mut(id) {
+                    appearance.color = color;
+                }
+            }
+        }
+    }
+
+    fn guidance(&mut self) {
+        let store = &mut self.store;
+        for (id, guidance) in store.guidances.iter() {
+            let target_pos = match guidance.target {
+                Some(Target::Position(position)) => Some(position),
+                Some(Target::Entity(target)) => store
+                    .rigidbodies
+                    .get(target)
+                    .map(|rigidbody| rigidbody.position),
+                None => None,
+            };
+            let reached = match (target_pos, store.rigidbodies.get(id)) {
+                (Some(target_pos), Some(rigidbody)) => {
+                    (rigidbody.position - target_pos).length() <= guidance.precision
+                }
+                _ => false,
+            };
+            if reached {
+                if let Some(impact) = store.impacts.get_mut(id) {
+                    impact.triggered = true;
+                }
+            }
+        }
+    }
+
+    fn impacts(&mut self, commands: &mut Commands) {
+        let store = &mut self.store;
+        for (id, impact) in store.impacts.iter_mut() {
+            if impact.triggered {
+                if let Some(rigidbody) = store.rigidbodies.get(id) {
+                    commands.despawn(id);
+                    impact.attack.perform(id, rigidbody.position, commands);
+                }
+            }
+        }
+    }
+
+    fn explosions(&mut self, delta_time: f32, commands: &mut Commands) {
+        let store = &mut self.store;
+        for (id, explosion) in store.explosions.iter() {
+            if let Some(rigidbody) = store.rigidbodies.get_mut(id) {
+                rigidbody.collider.radius += delta_time * explosion.speed;
+                if rigidbody.collider.radius > explosion.radius {
+                    commands.despawn(id);
+                }
+            }
+        }
+    }
+
+    fn shockwaves(&mut self, commands: &mut Commands) {
+        for id in self.store.explosions.ids() {
+            let (center, radius) = match self.store.rigidbodies.get(id) {
+                Some(rigidbody) => (rigidbody.position, rigidbody.collider.radius),
+                None => continue,
+            };
+            let (strength, damage, hit) = match &self.store.explosions.get(id).unwrap().shockwave {
+                Some(shockwave) => (shockwave.strength, shockwave.damage, shockwave.hit.clone()),
+                None => continue,
+            };
+            let faction = *self.store.factions.get(id).unwrap();
+            for target in self.opponents_alive(faction) {
+                if hit.contains(&target) {
+                    continue;
+                }
+                let rigidbody = self.store.rigidbodies.get_mut(target).unwrap();
+                let offset = rigidbody.position - center;
+                if offset.length() > radius + rigidbody.collider.radius {
+                    continue;
+                }
+                if !rigidbody.is_kinematic {
+                    rigidbody.velocity += offset.normalize_or_zero() * strength;
+                }
+                if let Some(Explosion {
+                    shockwave: Some(shockwave),
+                    ..
+                }) = self.store.explosions.get_mut(id)
+                {
+                    shockwave.hit.push(target);
+                }
+                if target == self.player.id {
+                    self.damage_player(
+                        damage,
+                        DamageSource::Entity(EntityKind::Explosion),
+                        &mut commands.events,
+                    );
+                } else {
+                    self.store
+                        .damage(target, damage, DamageSource::Entity(EntityKind::Explosion));
+                }
+            }
+        }
+    }
+
+    fn dashes(&mut self, delta_time: f32) {
+        let store = &mut self.store;
+        let mut finished = Vec::new();
+        for (id, dash) in store.dashes.iter_mut() {
+            dash.time_left -= delta_time;
+            if dash.time_left <= 0.0 {
+                if let Some(rigidbody) = store.rigidbodies.get_mut(id) {
+                    rigidbody.mass = dash.mass;
+                    rigidbody.is_kinematic = dash.is_kinematic;
+                }
+                finished.push(id);
+            }
+        }
+        for id in finished {
+            store.dashes.remove(id);
+        }
+    }
+
+    fn shields(&mut self, delta_time: f32) {
+        let mut expired = Vec::new();
+        for (id, shield) in self.store.shields.iter_mut() {
+            shield.time_left -= delta_time;
+            if shield.time_left <= 0.0 {
+                expired.push(id);
+            }
+        }
+        for id in expired {
+            self.store.shields.remove(id);
+        }
+    }
+
+    fn stuns(&mut self, delta_time: f32) {
+        let mut recovered = Vec::new();
+        for (id, stun) in self.store.stuns.iter_mut() {
+            stun.time_left -= delta_time;
+            if stun.time_left <= 0.0 {
+                recovered.push(id);
+            }
+        }
+        for id in recovered {
+            self.store.stuns.remove(id);
+        }
+    }
+
+    pub fn find_closest(
+        &self,
+        origin: Vec2,
+        target_types: &[EntityType],
+    ) -> Option<(EntityId, Vec2)> {
+        self.store
+            .factions
+            .iter()
+            .filter(|(_, faction)| target_types.contains(faction))
+            .filter_map(|(id, _)| {
+                self.store
+                    .rigidbodies
+                    .get(id)
+                    .map(|rigidbody| (id, rigidbody.position))
+            })
+            .min_by(|(_, pos_a), (_, pos_b)| {
+                let dist_a = (*pos_a - origin).length();
+                let dist_b = (*pos_b - origin).length();
                 dist_a.partial_cmp(&dist_b).unwrap()
             })
     }
 
-    fn entities_type(&self, target_types: Vec<EntityType>) -> impl Iterator<Item = &Entity> {
-        let include_player = target_types.contains(&self.player.entity_type());
-        self.entities()
-            .filter(move |entity| target_types.contains(&entity.entity_type()))
-            .map(|entity| entity.entity())
-            .chain(if include_player {
-                vec![self.player.entity()].into_iter()
-            } else {
-                vec![].into_iter()
+    fn find_closest_alive(
+        &self,
+        origin: Vec2,
+        target_types: &[EntityType],
+    ) -> Option<(EntityId, Vec2)> {
+        target_types
+            .iter()
+            .flat_map(|&faction| self.faction_alive(faction))
+            .filter_map(|id| {
+                self.store
+                    .rigidbodies
+                    .get(id)
+                    .map(|rigidbody| (id, rigidbody.position))
+            })
+            .min_by(|(_, pos_a), (_, pos_b)| {
+                let dist_a = (*pos_a - origin).length();
+                let dist_b = (*pos_b - origin).length();
+                dist_a.partial_cmp(&dist_b).unwrap()
             })
     }
 
-    fn area_effects(&mut self, delta_time: f32) {
+    fn opponents_alive(&self, faction: EntityType) -> Vec<EntityId> {
+        faction
+            .opponents()
+            .into_iter()
+            .flat_map(|faction| self.faction_alive(faction))
+            .collect()
+    }
+
+    pub fn faction_alive(&self, faction: EntityType) -> Vec<EntityId> {
+        self.store
+            .factions
+            .iter()
+            .filter(|&(id, &entity_faction)| entity_faction == faction && self.store.is_alive(id))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    fn area_effects(&mut self, delta_time: f32, commands: &mut Commands) {
+        let player_alive = self.player_alive();
+        let player_body = self.player_body().clone();
         for area_effect in &mut self.area_effects {
             area_effect.lifetime.change(-delta_time);
 
-            if self.player.entity.is_alive() {
-                let distance =
-                    (area_effect.position - self.player.entity.rigidbody.position).length();
-                if distance <= self.player.entity.rigidbody.collider.radius + area_effect.radius {
+            if player_alive {
+                let distance = (area_effect.position - player_body.position).length();
+                if distance <= player_body.collider.radius + area_effect.radius {
+                    if !area_effect.collected {
+                        area_effect.collected = true;
+                        commands.event(Event::PickupCollected {
+                            pickup: match &area_effect.effect {
+                                Effect::Heal { .. } => PickupKind::HealZone,
+                            },
+                            position: area_effect.position,
+                        });
+                    }
                     match &area_effect.effect {
                         Effect::Heal { heal } => {
-                            self.player.entity.health.change(*heal * delta_time);
+                            if let Some(health) = self.store.healths.get_mut(self.player.id) {
+                                let hp = health.hp;
+                                health.change(*heal * delta_time);
+                                self.stats.healing_received += health.hp - hp;
+                            }
                         }
                     }
                 }
@@ -117,130 +427,636 @@ impl Model {
     }
 
     fn decide_movement(&mut self, delta_time: f32) {
-        let mut targets = HashMap::new();
-        for (index, entity) in self.entities().enumerate() {
-            let target_types = entity.movement_targets();
-            let entity_pos = entity.rigidbody.position;
-            if let Some(target_pos) = self.find_closest(entity_pos, target_types) {
-                targets.insert(index, target_pos);
+        self.player_movement(delta_time);
+        self.homing(delta_time);
+        for id in self.store.ais.ids() {
+            if self.store.dashes.get(id).is_some() || self.is_stunned(id) {
+                continue;
+            }
+            let (position, speed) =
+                match (self.store.rigidbodies.get(id), self.store.movements.get(id)) {
+                    (Some(rigidbody), Some(movement)) => (rigidbody.position, movement.speed),
+                    _ => continue,
+                };
+            let target_pos = match self.store.ais.get(id).cloned() {
+                Some(Ai::Chase { targets }) => self
+                    .find_closest(position, &targets)
+                    .map(|(_, target_pos)| target_pos),
+                Some(Ai::Follow { leader, distance }) => {
+                    let leader = leader
+                        .filter(|&leader| self.store.is_valid(leader))
+                        .or_else(|| {
+                            self.find_closest(position, &[EntityType::Player])
+                                .map(|(leader, _)| leader)
+                        });
+                    if let Some(Ai::Follow {
+                        leader: current_leader,
+                        ..
+                    }) = self.store.ais.get_mut(id)
+                    {
+                        *current_leader = leader;
+                    }
+                    leader
+                        .and_then(|leader| self.store.rigidbodies.get(leader))
+                        .map(|rigidbody| rigidbody.position)
+                        .filter(|&leader_pos| (leader_pos - position).length() > distance)
+                }
+                None => None,
+            };
+            if let Some(target_pos) = target_pos {
+                let rigidbody = self.store.rigidbodies.get_mut(id).unwrap();
+                let target_direction = target_pos - rigidbody.position;
+                let target_velocity = target_direction.normalize() * speed;
+                rigidbody.velocity += (target_velocity - rigidbody.velocity) * delta_time;
             }
-        }
-        self.player.decide_movement(None, delta_time);
-        for (index, entity) in self.entities_mut().enumerate() {
-            entity.decide_movement(targets.get(&index).copied(), delta_time);
         }
     }
 
-    pub fn entities(&self) -> impl Iterator<Item = &Box<dyn EntityObject>> {
-        self.enemies.iter().chain(self.minions.iter())
+    fn homing(&mut self, delta_time: f32) {
+        for id in self.store.guidances.ids() {
+            let guidance = self.store.guidances.get(id).unwrap();
+            let homing = match &guidance.homing {
+                Some(homing) => homing.clone(),
+                None => continue,
+            };
+            let position = match self.store.rigidbodies.get(id) {
+                Some(rigidbody) => rigidbody.position,
+                None => continue,
+            };
+
+            // Keep the current lock while the target lives, otherwise find a new one
+            let locked = match guidance.target {
+                Some(Target::Entity(target)) if self.store.is_alive(target) => Some(target),
+                _ => None,
+            };
+            let target = locked.or_else(|| {
+                self.find_closest_alive(position, &homing.targets)
+                    .map(|(target, _)| target)
+            });
+            self.store.guidances.get_mut(id).unwrap().target = target.map(Target::Entity);
+
+            let target_pos = match target.and_then(|target| self.store.rigidbodies.get(target)) {
+                Some(rigidbody) => rigidbody.position,
+                None => continue,
+            };
+            // Right on top of the target there is no direction to turn towards
+            let target_direction = match (target_pos - position).try_normalize() {
+                Some(direction) => direction,
+                None => continue,
+            };
+            let rigidbody = self.store.rigidbodies.get_mut(id).unwrap();
+            let direction = rigidbody
+                .velocity
+                .try_normalize()
+                .unwrap_or(target_direction);
+            let max_turn = homing.turn_rate * delta_time;
+            let turn = direction
+                .angle_between(target_direction)
+                .clamp(-max_turn, max_turn);
+            let direction = Vec2::from_angle(turn).rotate(direction);
+            let speed = (rigidbody.velocity.length() + homing.acceleration * delta_time)
+                .min(homing.max_speed);
+            rigidbody.velocity = direction * speed;
+        }
     }
 
-    fn entities_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn EntityObject>> {
-        self.enemies.iter_mut().chain(self.minions.iter_mut())
+    fn player_movement(&mut self, delta_time: f32) {
+        if !self.player_alive() {
+            return;
+        }
+        let player = &mut self.player;
+        let body = self.store.rigidbodies.get_mut(player.id).unwrap();
+
+        // Calculate head target velocity
+        let direction = player.head.position - body.position;
+        let target = player.head_target - body.position;
+        let angle = direction.angle_between(target).abs();
+        let speed = angle.min(0.2) / 0.2;
+        let direction = vec2(direction.y, -direction.x).normalize();
+        let signum = direction.dot(target).signum();
+        let direction = direction * signum * speed;
+        player.target_head_velocity = direction * HEAD_SPEED + body.velocity;
+
+        // Accelerate towards target velocity
+        let target_change = player.target_body_velocity - body.velocity;
+        body.velocity += target_change * BODY_ACCELERATION * delta_time;
+
+        let target_change = player.target_head_velocity - player.head.velocity;
+        player.head.velocity += target_change * HEAD_ACCELERATION * delta_time;
     }
 
     fn move_entities(&mut self, delta_time: f32) {
-        self.player.movement(delta_time);
-        for entity in self.entities_mut() {
-            entity.movement(delta_time);
+        let player_position = self.player_body().position;
+        let store = &mut self.store;
+        for (id, rigidbody) in store.rigidbodies.iter_mut() {
+            rigidbody.movement(delta_time);
+            let speed = store
+                .movements
+                .get(id)
+                .map_or(0.0, |movement| movement.speed);
+            if rigidbody.velocity.length() > speed && store.dashes.get(id).is_none() {
+                rigidbody.drag(delta_time);
+            }
+        }
+
+        // Clamp distance between body and head
+        let body_position = self.player_body().position;
+        let head = &mut self.player.head;
+        head.movement(delta_time);
+        let offset = head.position - body_position;
+        let distance = offset.length() - self.player.chain_length;
+        head.position -= offset.normalize_or_zero() * distance;
+
+        if self.player_alive() {
+            self.stats.distance_traveled += (body_position - player_position).length();
+        }
+    }
+
+    fn on_collide(&mut self, id: EntityId) {
+        if let Some(impact) = self.store.impacts.get_mut(id) {
+            impact.triggered = true;
         }
     }
 
     fn collide(&mut self, commands: &mut Commands) {
         // Collide bounds
         let bounds = self.bounds;
-        if self.player.collide_bounds(&bounds) {
-            self.player.on_collide_bounds(commands);
-        }
-        self.player.head.bounce_bounds(&bounds);
-        for entity in self.entities_mut() {
-            if entity.collide_bounds(&bounds) {
-                entity.on_collide_bounds(commands);
+        let bounciness = self.bounds_bounciness();
+        let store = &mut self.store;
+        for (id, rigidbody) in store.rigidbodies.iter_mut() {
+            if rigidbody.bounce_bounds(&bounds, bounciness) {
+                commands.event(Event::Sound {
+                    sound: EventSound::Bounce,
+                    position: rigidbody.position,
+                    intensity: rigidbody.velocity.length(),
+                });
+                if let Some(lifetime) = store.lifetimes.get_mut(id) {
+                    if lifetime.expire_on_bounds {
+                        lifetime.time.kill();
+                    }
+                }
+                if let Some(impact) = store.impacts.get_mut(id) {
+                    if impact.trigger_on_bounds {
+                        impact.triggered = true;
+                    }
+                }
             }
         }
+        self.player.head.bounce_bounds(&bounds, bounciness);
+        self.reflect_projectiles(commands);
 
         // Collide player body
-        for enemy in self.enemies.iter_mut().filter(|enemy| enemy.is_alive()) {
-            if let Some(hit_info) = self.player.collide(enemy) {
-                self.player.on_collide(commands);
-                enemy.on_collide(commands);
-                let player_alive = self.player.entity.is_alive();
-                self.player.entity.health.change(-hit_info.hit_self);
-                commands.spawn_particles(hit_info.contact, hit_info.hit_self * 5.0, PLAYER_COLOR);
-                enemy.health.change(-hit_info.hit_other);
-                commands.spawn_particles(hit_info.contact, hit_info.hit_other, enemy.color);
+        let player_id = self.player.id;
+        for enemy_id in self.faction_alive(EntityType::Enemy) {
+            if !self.store.is_alive(enemy_id) {
+                continue;
+            }
+            let hit_self = self.store.hit_strength(player_id);
+            let hit_other = self.store.hit_strength(enemy_id);
+            let (player_body, enemy_body) = self
+                .store
+                .rigidbodies
+                .get_pair_mut(player_id, enemy_id)
+                .unwrap();
+            if let Some(hit_info) = player_body.collide(enemy_body, hit_self, hit_other) {
+                self.on_collide(enemy_id);
+                let enemy_kind = *self.store.kinds.get(enemy_id).unwrap();
+                let enemy_color = self.store.appearances.get(enemy_id).unwrap().color;
+                self.damage_player(
+                    hit_info.hit_self,
+                    DamageSource::Entity(enemy_kind),
+                    &mut commands.events,
+                );
+                self.stats.record_hit(hit_info.hit_other, false);
+                commands.spawn_particles(
+                    ParticlePreset::HitSparks,
+                    hit_info.contact,
+                    hit_info.hit_self * 5.0,
+                    PLAYER_COLOR,
+                );
+                self.store
+                    .damage(enemy_id, hit_info.hit_other, DamageSource::PlayerBody);
+                commands.spawn_particles(
+                    ParticlePreset::HitSparks,
+                    hit_info.contact,
+                    hit_info.hit_other,
+                    enemy_color,
+                );
                 commands.event(Event::Sound {
                     sound: EventSound::BodyHit,
+                    position: hit_info.contact,
+                    intensity: hit_info.hit_self.max(hit_info.hit_other),
                 });
-                if player_alive && !self.player.entity.is_alive() {
-                    commands.event(Event::Sound {
-                        sound: EventSound::Death,
-                    })
-                }
             }
         }
 
         // Collide player head
-        for enemy in self.enemies.iter_mut().filter(|enemy| enemy.is_alive()) {
-            if let Some(hit_info) = self.player.head.collide(&mut enemy.rigidbody, None, None) {
-                enemy.on_collide(commands);
-                enemy.health.change(-hit_info.hit_other);
-                commands.spawn_particles(hit_info.contact, hit_info.hit_other, enemy.color);
+        for enemy_id in self.faction_alive(EntityType::Enemy) {
+            if !self.store.is_alive(enemy_id) {
+                continue;
+            }
+            let enemy_body = self.store.rigidbodies.get_mut(enemy_id).unwrap();
+            if let Some(hit_info) = self.player.head.collide(enemy_body, None, None) {
+                self.on_collide(enemy_id);
+                let enemy_color = self.store.appearances.get(enemy_id).unwrap().color;
+                self.store
+                    .damage(enemy_id, hit_info.hit_other, DamageSource::PlayerHead);
+                self.stats.record_hit(hit_info.hit_other, true);
+                if let Some(attacker) = self.store.attacks.get_mut(enemy_id) {
+                    attacker.attack.interrupt(hit_info.hit_other);
+                }
+                self.impact(enemy_id, hit_info.hit_other);
+                commands.spawn_particles(
+                    ParticlePreset::HitSparks,
+                    hit_info.contact,
+                    hit_info.hit_other,
+                    enemy_color,
+                );
+                commands.event(Event::HeadHit {
+                    strength: hit_info.hit_other,
+                    position: hit_info.contact,
+                });
                 commands.event(Event::Sound {
                     sound: EventSound::HeadHit,
+                    position: hit_info.contact,
+                    intensity: hit_info.hit_other,
                 });
             }
         }
 
         // Collide minions
-        for enemy in self.enemies.iter_mut().filter(|enemy| enemy.is_alive()) {
-            for minion in self.minions.iter_mut().filter(|minion| minion.is_alive()) {
-                if let Some(hit_info) = enemy.collide(minion) {
-                    minion.on_collide(commands);
-                    enemy.on_collide(commands);
-                    enemy.health.change(-hit_info.hit_self);
-                    commands.spawn_particles(hit_info.contact, hit_info.hit_self, enemy.color);
-                    minion.health.change(-hit_info.hit_other);
-                    commands.spawn_particles(hit_info.contact, hit_info.hit_other, minion.color);
+        let minions = self.faction_alive(EntityType::Minion);
+        for enemy_id in self.faction_alive(EntityType::Enemy) {
+            for &minion_id in &minions {
+                if !self.store.is_alive(enemy_id) || !self.store.is_alive(minion_id) {
+                    continue;
+                }
+                let hit_self = self.store.hit_strength(enemy_id);
+                let hit_other = self.store.hit_strength(minion_id);
+                let (enemy_body, minion_body) = self
+                    .store
+                    .rigidbodies
+                    .get_pair_mut(enemy_id, minion_id)
+                    .unwrap();
+                if let Some(hit_info) = enemy_body.collide(minion_body, hit_self, hit_other) {
+                    self.on_collide(minion_id);
+                    self.on_collide(enemy_id);
+                    let enemy_kind = *self.store.kinds.get(enemy_id).unwrap();
+                    let minion_kind = *self.store.kinds.get(minion_id).unwrap();
+                    let enemy_color = self.store.appearances.get(enemy_id).unwrap().color;
+                    let minion_color = self.store.appearances.get(minion_id).unwrap().color;
+                    self.store.damage(
+                        enemy_id,
+                        hit_info.hit_self,
+                        DamageSource::Entity(minion_kind),
+                    );
+                    commands.spawn_particles(
+                        ParticlePreset::HitSparks,
+                        hit_info.contact,
+                        hit_info.hit_self,
+                        enemy_color,
+                    );
+                    self.store.damage(
+                        minion_id,
+                        hit_info.hit_other,
+                        DamageSource::Entity(enemy_kind),
+                    );
+                    commands.spawn_particles(
+                        ParticlePreset::HitSparks,
+                        hit_info.contact,
+                        hit_info.hit_other,
+                        minion_color,
+                    );
                     commands.event(Event::Sound {
                         sound: EventSound::HeadHit,
+                        position: hit_info.contact,
+                        intensity: hit_info.hit_self.max(hit_info.hit_other),
                     });
                 }
             }
         }
     }
 
-    fn check_dead(&mut self, delta_time: f32, commands: &mut Commands) {
-        let mut dead_enemies = Vec::new();
-        for (index, entity) in self.entities_mut().enumerate() {
-            if entity.destroy {
-                dead_enemies.push(index);
-            } else if !entity.is_alive() {
-                match entity.dead(delta_time) {
-                    DeadState::Destroy => dead_enemies.push(index),
-                    DeadState::Corpse => {
-                        dead_enemies.push(index);
-                        commands.spawn_entity(
-                            Box::new(CorpseInfo::new(
-                                entity.entity_type(),
-                                Health::new(CORPSE_LIFETIME),
-                                entity.rigidbody.velocity,
-                                entity.entity_info(),
-                            ))
-                            .into_entity_object(entity.rigidbody.position),
-                        );
+    pub fn damage_player(&mut self, amount: f32, source: DamageSource, events: &mut Vec<Event>) {
+        if self.god_mode {
+            return;
+        }
+        let player_alive = self.player_alive();
+        self.store.damage(self.player.id, amount, source);
+        events.push(Event::PlayerDamaged { amount, source });
+        self.stats.record_damage(amount, source);
+        if player_alive && !self.player_alive() {
+            events.push(Event::Sound {
+                sound: EventSound::Death,
+                position: self.player_body().position,
+                intensity: 1.0,
+            });
+            events.push(Event::PlayerDied {
+                stage: self.current_stage,
+            });
+        }
+    }
+
+    /// Damages every opponent of the owner whose collider touches the beam
+    pub fn hitscan(
+        &mut self,
+        owner: EntityId,
+        origin: Vec2,
+        end: Vec2,
+        width: f32,
+        damage: f32,
+        events: &mut Vec<Event>,
+    ) {
+        let (owner_kind, owner_faction) =
+            match (self.store.kinds.get(owner), self.store.factions.get(owner)) {
+                (Some(&kind), Some(&faction)) => (kind, faction),
+                _ => return,
+            };
+        let range = (end - origin).length();
+        let direction = (end - origin).normalize_or_zero();
+        for target in self.opponents_alive(owner_faction) {
+            let rigidbody = self.store.rigidbodies.get(target).unwrap();
+            let along = (rigidbody.position - origin)
+                .dot(direction)
+                .clamp(0.0, range);
+            let closest = origin + direction * along;
+            if (rigidbody.position - closest).length() > rigidbody.collider.radius + width / 2.0 {
+                continue;
+            }
+            let source = DamageSource::Entity(owner_kind);
+            if target == self.player.id {
+                self.damage_player(damage, source, events);
+            } else {
+                self.store.damage(target, damage, source);
+            }
+            events.push(Event::Sound {
+                sound: EventSound::BodyHit,
+                position: closest,
+                intensity: damage,
+            });
+        }
+        events.push(Event::BeamFired { start: origin, end });
+    }
+
+    /// Shields send incoming projectiles back and switch them to the shield's side
+    fn reflect_projectiles(&mut self, commands: &mut Commands) {
+        for id in self.store.shields.ids() {
+            let shield = self.store.shields.get(id).unwrap().clone();
+            let (center, radius) = match self.store.rigidbodies.get(id) {
+                Some(rigidbody) => (
+                    rigidbody.position,
+                    rigidbody.collider.radius + shield.radius,
+                ),
+                None => continue,
+            };
+            let faction = *self.store.factions.get(id).unwrap();
+            let projectiles: Vec<EntityId> = self
+                .store
+                .kinds
+                .iter()
+                .filter(|(_, kind)| matches!(kind, EntityKind::Projectile | EntityKind::Missile))
+                .map(|(projectile, _)| projectile)
+                .filter(|&projectile| {
+                    self.store.is_alive(projectile)
+                        && self.store.factions.get(projectile) != Some(&faction)
+                })
+                .collect();
+            for projectile in projectiles {
+                let rigidbody = self.store.rigidbodies.get_mut(projectile).unwrap();
+                let offset = rigidbody.position - center;
+                let normal = offset.normalize_or_zero();
+                if offset.length() > radius + rigidbody.collider.radius
+                    || normal.angle_between(shield.direction).abs() > shield.arc / 2.0
+                    || rigidbody.velocity.dot(normal) >= 0.0
+                {
+                    continue;
+                }
+                rigidbody.velocity -= 2.0 * rigidbody.velocity.dot(normal) * normal;
+                commands.event(Event::Sound {
+                    sound: EventSound::Bounce,
+                    position: rigidbody.position,
+                    intensity: rigidbody.velocity.length(),
+                });
+                self.store.factions.insert(projectile, faction);
+                if let Some(guidance) = self.store.guidances.get_mut(projectile) {
+                    guidance.target = None;
+                    if let Some(homing) = &mut guidance.homing {
+                        homing.targets = faction.opponents();
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_dead(&mut self, commands: &mut Commands) {
+        for id in self.store.healths.ids() {
+            if id == self.player.id || self.store.is_alive(id) || commands.is_despawning(id) {
+                continue;
+            }
+            if self.leaves_corpse(id) {
+                let entity_kind = *self.store.kinds.get(id).unwrap();
+                self.stats.record_kill(entity_kind);
+                commands.event(Event::EntityDied {
+                    entity_kind,
+                    cause: self
+                        .store
+                        .damage_sources
+                        .get(id)
+                        .copied()
+                        .unwrap_or(DamageSource::Expired),
+                    position: self.store.rigidbodies.get(id).unwrap().position,
+                });
+                let rigidbody = self.store.rigidbodies.get(id).unwrap();
+                let color = self
+                    .store
+                    .appearances
+                    .get(id)
+                    .map_or(WHITE, |appearance| appearance.color);
+                commands.spawn_particles(
+                    ParticlePreset::DeathBurst,
+                    rigidbody.position,
+                    rigidbody.collider.radius,
+                    color,
+                );
+                if self.has_mutator(Mutator::ExplosiveDeaths) {
+                    if let EntityKind::Enemy(_) = entity_kind {
+                        self.death_explosion(rigidbody.position, commands);
                     }
-                    DeadState::Idle => (),
                 }
+                self.store.spawn_corpse(id);
             }
+            self.store.despawn(id);
         }
-        dead_enemies.reverse();
-        for dead_index in dead_enemies {
-            if dead_index >= self.enemies.len() {
-                self.minions.remove(dead_index - self.enemies.len());
-            } else {
-                self.enemies.remove(dead_index);
+    }
+
+    /// Belongs to the enemies on purpose, it's the risk of the explosive deaths mutator
+    fn death_explosion(&self, position: Vec2, commands: &mut Commands) {
+        commands.spawn_entity(
+            EntityBlueprint::Explosion(ExplosionInfo::new(
+                EntityType::Enemy,
+                DEATH_EXPLOSION_RADIUS,
+                30.0,
+                100.0,
+            )),
+            position,
+            Vec2::ZERO,
+            None,
+        );
+        commands.spawn_particles(
+            ParticlePreset::Explosion,
+            position,
+            EXPLOSION_INTENSITY,
+            BOMB_COLOR,
+        );
+        commands.event(Event::Sound {
+            sound: EventSound::Explosion,
+            position,
+            intensity: EXPLOSION_INTENSITY,
+        });
+    }
+
+    fn leaves_corpse(&self, id: EntityId) -> bool {
+        match self.store.attacks.get(id) {
+            Some(attacker) if !attacker.attack.attack_time.is_alive() => {
+                !matches!(attacker.attack.attack_type, AttackType::Explode { .. })
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXED_DELTA_TIME: f32 = 1.0 / 60.0;
+    const SECONDS: usize = 60;
+    /// Waves only clear when their enemies die, so they are culled every few seconds
+    const KILL_INTERVAL: usize = 8;
+    /// Spawners are sampled this long after every wave start is reported
+    const SAMPLE_DELAYS: [f32; 2] = [0.5, 1.5];
+    /// A frame at the lowest tested frame rate plus a tick
+    const TOLERANCE: f32 = 1.0 / 30.0 + FIXED_DELTA_TIME + 1e-3;
+
+    #[derive(Default)]
+    struct Timeline {
+        /// Stage and the time it was reported to the frame side
+        waves: Vec<(usize, f32)>,
+        spawner_time_left: Vec<Option<f32>>,
+    }
+
+    /// Drives the model the way `main` does: input, commands and events once per frame,
+    /// then as many fixed ticks as the frame time adds up to
+    fn run_at_fps(fps: usize) -> Timeline {
+        let mut model = Model::new(1234);
+        model.god_mode = true;
+        let mut game_loop = GameLoop::new(FIXED_DELTA_TIME, 10);
+        let mut timeline = Timeline::default();
+        let kill_frames = KILL_INTERVAL * fps;
+        let delta_time = 1.0 / fps as f32;
+        let mut sample_frames = Vec::new();
+        for frame in 0..SECONDS * fps {
+            let time = frame as f32 * delta_time;
+            model.move_direction(Vec2::ZERO);
+            model.head_target(model.player.head.position);
+            // Like the console's `kill all`, which runs between frames
+            if frame > 0 && frame % kill_frames == 0 {
+                model.kill_enemies();
+            }
+            for event in model.events.drain(..) {
+                if let Event::NextWave { stage } = event {
+                    timeline.waves.push((stage, time));
+                    sample_frames.extend(
+                        SAMPLE_DELAYS
+                            .iter()
+                            .map(|&delay| frame + (delay * fps as f32) as usize),
+                    );
+                }
+            }
+
+            for _ in 0..game_loop.advance(delta_time) {
+                model.fixed_update(FIXED_DELTA_TIME);
+            }
+
+            if sample_frames.contains(&frame) {
+                timeline
+                    .spawner_time_left
+                    .push(model.spawners.first().map(|spawner| spawner.time_left));
             }
         }
+        timeline
+    }
+
+    #[test]
+    fn wave_timing_is_independent_of_frame_rate() {
+        let expected = run_at_fps(60);
+        assert_eq!(expected.waves.len(), SECONDS / KILL_INTERVAL + 1);
+        assert!(expected.spawner_time_left.iter().all(Option::is_some));
+        for &fps in &[30, 144] {
+            let timeline = run_at_fps(fps);
+            assert_eq!(timeline.waves.len(), expected.waves.len(), "{} fps", fps);
+            for (&(stage, time), &(expected_stage, expected_time)) in
+                timeline.waves.iter().zip(&expected.waves)
+            {
+                assert_eq!(stage, expected_stage);
+                assert!(
+                    (time - expected_time).abs() <= TOLERANCE,
+                    "{} fps: stage {} started at {}s instead of {}s",
+                    fps,
+                    stage,
+                    time,
+                    expected_time
+                );
+            }
+            assert_eq!(
+                timeline.spawner_time_left.len(),
+                expected.spawner_time_left.len()
+            );
+            for (time_left, expected_time_left) in timeline
+                .spawner_time_left
+                .iter()
+                .zip(&expected.spawner_time_left)
+            {
+                let (time_left, expected_time_left) =
+                    (time_left.unwrap(), expected_time_left.unwrap());
+                assert!(
+                    (time_left - expected_time_left).abs() <= TOLERANCE,
+                    "{} fps: spawner at {}s instead of {}s",
+                    fps,
+                    time_left,
+                    expected_time_left
+                );
+            }
+        }
+    }
+
+    /// Swings the head into a brute hard enough to stun it and stop the simulation
+    fn strong_head_hit(seed: u64) -> (Model, f32, bool) {
+        let mut model = Model::new(seed);
+        // A plain swing isn't fast enough for the default hit-stop threshold
+        model.impact_tuning.hit_stop_threshold = 50.0;
+        // The head swings around the body on its chain, so the brute waits a quarter turn ahead
+        let target = model.player_body().position + vec2(0.0, model.player.chain_length);
+        model.spawn_enemies(EnemyKind::Brute, 1, target);
+        model.player.head.velocity = vec2(0.0, 600.0);
+        let (mut hit_stop, mut stunned) = (0.0f32, false);
+        for _ in 0..120 {
+            model.head_target(target);
+            model.fixed_update(FIXED_DELTA_TIME);
+            hit_stop = hit_stop.max(model.hit_stop);
+            stunned |= model.store.stuns.iter().next().is_some();
+        }
+        (model, hit_stop, stunned)
+    }
+
+    #[test]
+    fn hit_stop_and_stun_replay_identically() {
+        let (first, hit_stop, stunned) = strong_head_hit(1234);
+        assert!(hit_stop > 0.0 && stunned);
+        let (second, _, _) = strong_head_hit(1234);
+        assert_eq!(
+            serde_json::to_string(&first).unwrap(),
+            serde_json::to_string(&second).unwrap()
+        );
     }
 }
@@ -1,36 +1,301 @@
 use super::*;
 
+const SPAWN_TIME: f32 = 2.0;
+/// Threat points a stage spends are `BUDGET_PER_STAGE * stage ^ BUDGET_EXPONENT`
+const BUDGET_PER_STAGE: f32 = 2.0;
+const BUDGET_EXPONENT: f32 = 0.9;
+/// Roughly how many threat points go into one spawn group
+const GROUP_BUDGET: f32 = 10.0;
+const MAX_GROUPS: usize = 6;
+/// How much less likely an archetype gets for every copy already in the wave
+const VARIETY_PENALTY: f32 = 0.5;
+
+/// How an enemy kind takes part in wave generation
+pub struct Archetype {
+    pub kind: EnemyKind,
+    /// Threat points spent on one enemy
+    pub cost: f32,
+    /// The archetype is introduced on this stage
+    pub min_stage: usize,
+    pub weight: f32,
+}
+
+pub const ARCHETYPES: [Archetype; 8] = [
+    Archetype {
+        kind: EnemyKind::Melee,
+        cost: 1.0,
+        min_stage: 1,
+        weight: 2.0,
+    },
+    Archetype {
+        kind: EnemyKind::Ranger,
+        cost: 1.5,
+        min_stage: 1,
+        weight: 1.0,
+    },
+    Archetype {
+        kind: EnemyKind::Bomber,
+        cost: 2.0,
+        min_stage: 1,
+        weight: 0.5,
+    },
+    Archetype {
+        kind: EnemyKind::Charger,
+        cost: 2.0,
+        min_stage: 2,
+        weight: 0.7,
+    },
+    Archetype {
+        kind: EnemyKind::Launcher,
+        cost: 3.0,
+        min_stage: 4,
+        weight: 0.5,
+    },
+    Archetype {
+        kind: EnemyKind::Sniper,
+        cost: 3.0,
+        min_stage: 6,
+        weight: 0.4,
+    },
+    Archetype {
+        kind: EnemyKind::Guardian,
+        cost: 3.5,
+        min_stage: 8,
+        weight: 0.4,
+    },
+    Archetype {
+        kind: EnemyKind::Brute,
+        cost: 5.0,
+        min_stage: 10,
+        weight: 0.3,
+    },
+];
+
+/// Head hit strength needed to interrupt an enemy's wind-up
+const WINDUP_CANCEL_STRENGTH: f32 = 30.0;
+
 pub struct Wave {
     pub groups: Vec<WaveGroup>,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct WaveGroup {
-    pub entities: Vec<Box<dyn EntityObjectInfo>>,
+    pub entities: Vec<EntityBlueprint>,
     pub radius: f32,
 }
 
 impl Model {
-    pub fn next_wave(&mut self) {
+    pub fn next_wave(&mut self, commands: &mut Commands) {
+        if self.current_stage > 0 {
+            self.stats.wave_times.push(self.wave_time);
+        }
         self.current_stage += 1;
+        self.stats.stage = self.current_stage;
+        self.wave_time = 0.0;
+        self.roll_mutator(commands);
         let wave = self.generate_wave();
+        let spawn_time = SPAWN_TIME * self.spawn_time_scale();
         for group in wave.groups {
-            let group_position = Self::get_random_position_bounds(&self.spawn_bounds);
-            self.spawners.push(Spawner::new(group_position, group, 2.0));
+            let group_position = self.offscreen_position();
+            commands.emit_particles(
+                ParticleEmitter::new(
+                    ParticlePreset::SpawnerPortal,
+                    group_position,
+                    0.0,
+                    SPAWNER_COLOR,
+                )
+                .area(group.radius, spawn_time),
+            );
+            self.spawners
+                .push(Spawner::new(group_position, group, spawn_time));
         }
-        self.area_effects.push(AreaEffect {
-            position: Self::get_random_position_bounds(&self.spawn_bounds),
-            radius: macroquad::rand::gen_range(5.0, 15.0),
-            effect: Effect::Heal { heal: 10.0 },
-            lifetime: Health::new(10.0),
-        });
-        self.events.push(Event::NextWave {
+        if !self.has_mutator(Mutator::NoHealZones) {
+            self.spawn_heal_zone(commands);
+        }
+        commands.event(Event::NextWave {
             stage: self.current_stage,
         });
     }
 
-    fn generate_wave(&self) -> Wave {
-        // Prepare instances
-        let melee = Box::new(EnemyInfo::new(
+    fn spawn_heal_zone(&mut self, commands: &mut Commands) {
+        let heal_zone = AreaEffect {
+            position: self.rng.position_bounds(&self.spawn_bounds),
+            radius: self.rng.gen_range(5.0, 15.0),
+            effect: Effect::Heal {
+                heal: 10.0 * self.difficulty.scaling().heal,
+            },
+            lifetime: Health::new(10.0),
+            collected: false,
+        };
+        commands.emit_particles(
+            ParticleEmitter::new(
+                ParticlePreset::HealSparkles,
+                heal_zone.position,
+                0.0,
+                HEAL_ZONE_COLOR,
+            )
+            .area(heal_zone.radius, heal_zone.lifetime.hp_max),
+        );
+        self.area_effects.push(heal_zone);
+    }
+
+    /// A random spawn position out of view, or the farthest sampled one if none was
+    fn offscreen_position(&mut self) -> Vec2 {
+        const ATTEMPTS: usize = 10;
+        let view = self.view.unwrap_or_else(|| {
+            let center = self.player_body().position;
+            Bounds {
+                min: center - VIEW_HALF_SIZE,
+                max: center + VIEW_HALF_SIZE,
+            }
+        });
+        let center = (view.min + view.max) / 2.0;
+        let half_size = (view.max - view.min) / 2.0;
+        // Above 1 when outside of the view
+        let distance = |position: Vec2| ((position - center).abs() / half_size).max_element();
+
+        let mut farthest = self.rng.position_bounds(&self.spawn_bounds);
+        for _ in 1..ATTEMPTS {
+            if !view.contains(farthest) {
+                break;
+            }
+            let position = self.rng.position_bounds(&self.spawn_bounds);
+            if distance(position) > distance(farthest) {
+                farthest = position;
+            }
+        }
+        farthest
+    }
+
+    fn generate_wave(&mut self) -> Wave {
+        let budget = stage_budget(self.current_stage) * self.spawn_scale();
+        let plan = plan_wave(&mut self.rng, self.current_stage, budget);
+        log_info!(
+            "stage {}, budget {:.1}: {}",
+            self.current_stage,
+            budget,
+            describe_wave(&plan)
+        );
+        let groups = plan
+            .into_iter()
+            .map(|kinds| WaveGroup {
+                entities: kinds
+                    .into_iter()
+                    .map(|kind| self.scaled_enemy(kind))
+                    .collect(),
+                radius: self.rng.gen_range(10.0, 15.0),
+            })
+            .collect();
+        Wave { groups }
+    }
+}
+
+pub fn stage_budget(stage: usize) -> f32 {
+    BUDGET_PER_STAGE * (stage as f32).powf(BUDGET_EXPONENT)
+}
+
+/// Spends the budget on groups of enemies, an archetype introduced on this stage always shows up
+pub fn plan_wave(rng: &mut Rng, stage: usize, budget: f32) -> Vec<Vec<EnemyKind>> {
+    let available: Vec<&Archetype> = ARCHETYPES
+        .iter()
+        .filter(|archetype| stage >= archetype.min_stage)
+        .collect();
+    let cheapest = available
+        .iter()
+        .map(|archetype| archetype.cost)
+        .fold(f32::INFINITY, f32::min);
+    // Always spawn something, even when the budget is scaled down
+    let budget = budget.max(cheapest);
+    let groups_count = ((budget / GROUP_BUDGET).ceil() as usize).clamp(1, MAX_GROUPS);
+
+    let mut counts = [0; ARCHETYPES.len()];
+    let mut introduce = available
+        .iter()
+        .position(|archetype| archetype.min_stage == stage && archetype.cost <= budget);
+    let mut remaining = 0.0;
+    let mut groups = Vec::with_capacity(groups_count);
+    for _ in 0..groups_count {
+        remaining += budget / groups_count as f32;
+        let mut group = Vec::new();
+        loop {
+            let picked = match introduce.take() {
+                Some(index) => Some(index),
+                None => pick_archetype(rng, &available, &counts, remaining),
+            };
+            let index = match picked {
+                Some(index) => index,
+                None => break,
+            };
+            let archetype = available[index];
+            remaining -= archetype.cost;
+            counts[index] += 1;
+            group.push(archetype.kind);
+        }
+        if !group.is_empty() {
+            groups.push(group);
+        }
+    }
+    groups
+}
+
+/// A weighted pick among the affordable archetypes, favouring ones the wave has less of
+fn pick_archetype(
+    rng: &mut Rng,
+    available: &[&Archetype],
+    counts: &[usize],
+    remaining: f32,
+) -> Option<usize> {
+    let weights: Vec<(usize, f32)> = available
+        .iter()
+        .enumerate()
+        .filter(|(_, archetype)| archetype.cost <= remaining)
+        .map(|(index, archetype)| {
+            (
+                index,
+                archetype.weight / (1.0 + counts[index] as f32 * VARIETY_PENALTY),
+            )
+        })
+        .collect();
+    if weights.is_empty() {
+        return None;
+    }
+    let total_weight: f32 = weights.iter().map(|(_, weight)| weight).sum();
+    let mut random = rng.gen_range(0.0, total_weight);
+    for &(index, weight) in &weights {
+        random -= weight;
+        if random <= 0.0 {
+            return Some(index);
+        }
+    }
+    weights.last().map(|&(index, _)| index)
+}
+
+/// The composition of a planned wave, like "3 melee, 1 ranger | 2 charger"
+pub fn describe_wave(plan: &[Vec<EnemyKind>]) -> String {
+    plan.iter()
+        .map(|group| {
+            let mut kinds: Vec<(EnemyKind, usize)> = Vec::new();
+            for &kind in group {
+                match kinds.iter_mut().find(|(other, _)| *other == kind) {
+                    Some((_, count)) => *count += 1,
+                    None => kinds.push((kind, 1)),
+                }
+            }
+            kinds
+                .iter()
+                .map(|(kind, count)| format!("{} {}", count, kind))
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// The blueprint every enemy of that kind is spawned from
+pub fn enemy_blueprint(kind: EnemyKind) -> EntityBlueprint {
+    match kind {
+        EnemyKind::Melee => EntityBlueprint::Enemy(EnemyInfo::new(
+            EnemyKind::Melee,
             EnemyType::Crawler,
             EntityInfo::new(
                 Health::new(300.0),
@@ -41,14 +306,15 @@ impl Model {
                 MELEE_COLOR,
                 PhysicsMaterial::new(DRAG, BOUNCINESS),
             ),
-        ));
-        let ranger = Box::new(EnemyInfo::new(
+        )),
+        EnemyKind::Ranger => EntityBlueprint::Enemy(EnemyInfo::new(
+            EnemyKind::Ranger,
             EnemyType::Attacker {
                 attack: Attack {
                     attack_time: Health::new(1.0),
                     attack_type: AttackType::Shoot {
                         target_pos: vec2(0.0, 0.0),
-                        projectile: Box::new(ProjectileInfo::new(
+                        projectile: Box::new(EntityBlueprint::Projectile(ProjectileInfo::new(
                             Health::new(5.0),
                             EntityType::Enemy,
                             EntityInfo::new(
@@ -60,8 +326,16 @@ impl Model {
                                 PROJECTILE_COLOR,
                                 PhysicsMaterial::new(DRAG, BOUNCINESS),
                             ),
-                        )),
+                        ))),
                     },
+                    telegraph: Some(Telegraph::new(
+                        0.4,
+                        TelegraphShape::Line {
+                            length: 20.0,
+                            width: 0.5,
+                        },
+                        WINDUP_CANCEL_STRENGTH,
+                    )),
                 },
             },
             EntityInfo::new(
@@ -73,14 +347,15 @@ impl Model {
                 RANGER_COLOR,
                 PhysicsMaterial::new(DRAG, BOUNCINESS),
             ),
-        ));
-        let bomber = Box::new(EnemyInfo::new(
+        )),
+        EnemyKind::Bomber => EntityBlueprint::Enemy(EnemyInfo::new(
+            EnemyKind::Bomber,
             EnemyType::Attacker {
                 attack: Attack {
                     attack_time: Health::new(5.0),
                     attack_type: AttackType::Explode {
                         projectile_count: 5,
-                        projectile: Box::new(ProjectileInfo::new(
+                        projectile: Box::new(EntityBlueprint::Projectile(ProjectileInfo::new(
                             Health::new(3.0),
                             EntityType::Enemy,
                             EntityInfo::new(
@@ -92,8 +367,9 @@ impl Model {
                                 BOMB_COLOR,
                                 PhysicsMaterial::new(DRAG, BOUNCINESS),
                             ),
-                        )),
+                        ))),
                     },
+                    telegraph: None,
                 },
             },
             EntityInfo::new(
@@ -105,39 +381,217 @@ impl Model {
                 BOMBER_COLOR,
                 PhysicsMaterial::new(DRAG, BOUNCINESS),
             ),
-        ));
-
-        // Generate wave
-        use macroquad::rand::gen_range;
-        let max_groups = (self.current_stage as f32).sqrt().floor() as usize;
-        let groups_count = gen_range(max_groups.max(2) - 1, max_groups.max(1) + 1);
-        let mut wave = Wave {
-            groups: Vec::with_capacity(groups_count),
-        };
-        for _ in 0..groups_count {
-            let max_enemies = (self.current_stage as f32).sqrt().floor() as usize;
-            let enemies_count = gen_range(max_enemies.max(3) - 2, max_enemies.max(1) + 1);
-            let mut group = WaveGroup {
-                entities: Vec::with_capacity(enemies_count),
-                radius: gen_range(10.0, 15.0),
-            };
-            let weights = [(2.0, &melee), (1.0, &ranger), (0.5, &bomber)];
-            let total_weight: f32 = weights.iter().map(|(weight, _)| weight).sum();
-            for _ in 0..enemies_count {
-                let mut random = gen_range(0.0, 1.0);
-                let mut enemy = None;
-                for (weight, enemy_info) in &weights {
-                    let chance = *weight / total_weight;
-                    random -= chance;
-                    if random <= 0.0 {
-                        enemy = Some((**enemy_info).clone());
-                        break;
-                    }
+        )),
+        EnemyKind::Launcher => EntityBlueprint::Enemy(EnemyInfo::new(
+            EnemyKind::Launcher,
+            EnemyType::Attacker {
+                attack: Attack {
+                    attack_time: Health::new(4.0),
+                    attack_type: AttackType::Shoot {
+                        target_pos: vec2(0.0, 0.0),
+                        projectile: Box::new(EntityBlueprint::Missile(MissileInfo::homing(
+                            EntityInfo::new(
+                                Health::new(1.0),
+                                3.0,
+                                false,
+                                1.2,
+                                45.0,
+                                MISSILE_COLOR,
+                                PhysicsMaterial::new(DRAG, BOUNCINESS),
+                            ),
+                            EntityType::Enemy,
+                            Homing {
+                                targets: vec![EntityType::Player, EntityType::Minion],
+                                turn_rate: 2.0,
+                                acceleration: 30.0,
+                                max_speed: 45.0,
+                            },
+                            Health::new(4.0),
+                            1,
+                            EntityBlueprint::Explosion(ExplosionInfo::new(
+                                EntityType::Enemy,
+                                6.0,
+                                30.0,
+                                100.0,
+                            )),
+                        ))),
+                    },
+                    telegraph: Some(Telegraph::new(
+                        0.5,
+                        TelegraphShape::Cone {
+                            length: 15.0,
+                            angle: 0.6,
+                        },
+                        WINDUP_CANCEL_STRENGTH,
+                    )),
+                },
+            },
+            EntityInfo::new(
+                Health::new(200.0),
+                5.0,
+                false,
+                2.5,
+                15.0,
+                LAUNCHER_COLOR,
+                PhysicsMaterial::new(DRAG, BOUNCINESS),
+            ),
+        )),
+        EnemyKind::Charger => EntityBlueprint::Enemy(EnemyInfo::new(
+            EnemyKind::Charger,
+            EnemyType::Attacker {
+                attack: Attack {
+                    attack_time: Health::new(3.0),
+                    attack_type: AttackType::Dash {
+                        target_pos: vec2(0.0, 0.0),
+                        speed: 90.0,
+                        duration: 0.4,
+                        mass: 15.0,
+                    },
+                    telegraph: Some(Telegraph::new(
+                        0.5,
+                        TelegraphShape::Line {
+                            length: 36.0,
+                            width: 2.0,
+                        },
+                        WINDUP_CANCEL_STRENGTH,
+                    )),
+                },
+            },
+            EntityInfo::new(
+                Health::new(250.0),
+                5.0,
+                false,
+                2.2,
+                20.0,
+                CHARGER_COLOR,
+                PhysicsMaterial::new(DRAG, BOUNCINESS),
+            ),
+        )),
+        EnemyKind::Sniper => EntityBlueprint::Enemy(EnemyInfo::new(
+            EnemyKind::Sniper,
+            EnemyType::Attacker {
+                attack: Attack {
+                    attack_time: Health::new(4.0),
+                    attack_type: AttackType::Beam {
+                        target_pos: vec2(0.0, 0.0),
+                        range: 120.0,
+                        width: 1.5,
+                        damage: 40.0,
+                    },
+                    telegraph: Some(Telegraph::new(
+                        1.0,
+                        TelegraphShape::Line {
+                            length: 120.0,
+                            width: 1.5,
+                        },
+                        WINDUP_CANCEL_STRENGTH,
+                    )),
+                },
+            },
+            EntityInfo::new(
+                Health::new(150.0),
+                5.0,
+                false,
+                2.0,
+                15.0,
+                SNIPER_COLOR,
+                PhysicsMaterial::new(DRAG, BOUNCINESS),
+            ),
+        )),
+        EnemyKind::Guardian => EntityBlueprint::Enemy(EnemyInfo::new(
+            EnemyKind::Guardian,
+            EnemyType::Attacker {
+                attack: Attack {
+                    attack_time: Health::new(3.0),
+                    attack_type: AttackType::Shield {
+                        target_pos: vec2(0.0, 0.0),
+                        arc: 2.0,
+                        radius: 3.0,
+                        duration: 2.0,
+                    },
+                    telegraph: None,
+                },
+            },
+            EntityInfo::new(
+                Health::new(400.0),
+                5.0,
+                false,
+                2.5,
+                20.0,
+                GUARDIAN_COLOR,
+                PhysicsMaterial::new(DRAG, BOUNCINESS),
+            ),
+        )),
+        EnemyKind::Brute => EntityBlueprint::Enemy(EnemyInfo::new(
+            EnemyKind::Brute,
+            EnemyType::Attacker {
+                attack: Attack {
+                    attack_time: Health::new(5.0),
+                    attack_type: AttackType::Shockwave {
+                        shockwave: ExplosionInfo::shockwave(
+                            EntityType::Enemy,
+                            25.0,
+                            40.0,
+                            80.0,
+                            20.0,
+                        ),
+                    },
+                    telegraph: Some(Telegraph::new(
+                        0.8,
+                        TelegraphShape::Circle { radius: 25.0 },
+                        WINDUP_CANCEL_STRENGTH * 2.0,
+                    )),
+                },
+            },
+            EntityInfo::new(
+                Health::new(500.0),
+                10.0,
+                false,
+                3.5,
+                12.0,
+                BRUTE_COLOR,
+                PhysicsMaterial::new(DRAG, BOUNCINESS),
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cost(kind: EnemyKind) -> f32 {
+        ARCHETYPES
+            .iter()
+            .find(|archetype| archetype.kind == kind)
+            .unwrap()
+            .cost
+    }
+
+    #[test]
+    fn waves_stay_within_budget_and_introduce_archetypes() {
+        let mut rng = Rng::new(1234);
+        for stage in 1..=50 {
+            let budget = stage_budget(stage);
+            let plan = plan_wave(&mut rng, stage, budget);
+            let kinds: Vec<EnemyKind> = plan.iter().flatten().copied().collect();
+            assert!(!kinds.is_empty());
+            assert!(plan.len() <= MAX_GROUPS);
+            let spent: f32 = kinds.iter().map(|&kind| cost(kind)).sum();
+            assert!(
+                spent <= budget + 1e-3,
+                "stage {}: {}",
+                stage,
+                describe_wave(&plan)
+            );
+            for archetype in &ARCHETYPES {
+                let count = kinds.iter().filter(|&&kind| kind == archetype.kind).count();
+                if stage < archetype.min_stage {
+                    assert_eq!(count, 0);
+                } else if stage == archetype.min_stage && stage > 1 {
+                    assert!(count > 0, "stage {}: {}", stage, describe_wave(&plan));
                 }
-                group.entities.push(enemy.unwrap());
             }
-            wave.groups.push(group);
         }
-        wave
     }
 }
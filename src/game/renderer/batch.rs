@@ -0,0 +1,109 @@
+use super::*;
+use macroquad::models::{draw_mesh, Mesh, Vertex};
+
+const OUTLINE_THICKNESS: f32 = 0.2;
+const MIN_SEGMENTS: usize = 8;
+const MAX_SEGMENTS: usize = 64;
+const SEGMENTS_PER_PIXEL: f32 = 0.5;
+// Macroquad clamps bigger draw calls
+const MAX_VERTICES: usize = 10000;
+const MAX_INDICES: usize = 5000;
+
+/// Collects circles into one mesh that is drawn with a single call
+pub struct CircleBatch {
+    mesh: Mesh,
+    pixels_per_unit: f32,
+}
+
+impl CircleBatch {
+    pub fn new(camera: &Camera2D) -> Self {
+        Self {
+            mesh: Mesh {
+                vertices: Vec::with_capacity(MAX_VERTICES),
+                indices: Vec::with_capacity(MAX_INDICES),
+                texture: None,
+            },
+            pixels_per_unit: camera.zoom.x.abs() * screen_width() / 2.0,
+        }
+    }
+
+    /// Fewer segments for circles that are small on the screen
+    fn segments(&self, radius: f32) -> usize {
+        let screen_radius = radius * self.pixels_per_unit;
+        ((screen_radius * SEGMENTS_PER_PIXEL).ceil() as usize).clamp(MIN_SEGMENTS, MAX_SEGMENTS)
+    }
+
+    fn reserve(&mut self, vertices: usize, indices: usize) {
+        if self.mesh.vertices.len() + vertices > MAX_VERTICES
+            || self.mesh.indices.len() + indices > MAX_INDICES
+        {
+            self.flush();
+        }
+    }
+
+    pub fn circle(&mut self, position: Vec2, radius: f32, color: Color) {
+        if radius <= 0.0 {
+            return;
+        }
+        let segments = self.segments(radius);
+        self.reserve(segments + 1, segments * 3);
+        let start = self.mesh.vertices.len() as u16;
+        self.mesh.vertices.push(vertex(position, color));
+        for i in 0..segments {
+            let point = position + ring_point(i, segments) * radius;
+            self.mesh.vertices.push(vertex(point, color));
+            let next = (i + 1) % segments;
+            self.mesh.indices.extend_from_slice(&[
+                start,
+                start + 1 + i as u16,
+                start + 1 + next as u16,
+            ]);
+        }
+    }
+
+    pub fn outline(&mut self, position: Vec2, radius: f32, color: Color) {
+        if radius <= 0.0 {
+            return;
+        }
+        let segments = self.segments(radius);
+        self.reserve(segments * 2, segments * 6);
+        let start = self.mesh.vertices.len() as u16;
+        let inner = (radius - OUTLINE_THICKNESS / 2.0).max(0.0);
+        let outer = radius + OUTLINE_THICKNESS / 2.0;
+        for i in 0..segments {
+            let direction = ring_point(i, segments);
+            for &ring_radius in &[inner, outer] {
+                let point = position + direction * ring_radius;
+                self.mesh.vertices.push(vertex(point, color));
+            }
+            let (a, b) = (
+                start + 2 * i as u16,
+                start + 2 * ((i + 1) % segments) as u16,
+            );
+            self.mesh
+                .indices
+                .extend_from_slice(&[a, a + 1, b + 1, a, b + 1, b]);
+        }
+    }
+
+    /// Draws everything collected so far, later draws appear on top
+    pub fn flush(&mut self) {
+        if !self.mesh.indices.is_empty() {
+            draw_mesh(&self.mesh);
+        }
+        self.mesh.vertices.clear();
+        self.mesh.indices.clear();
+    }
+}
+
+fn vertex(position: Vec2, color: Color) -> Vertex {
+    Vertex {
+        position: position.extend(0.0),
+        uv: Vec2::ZERO,
+        color,
+    }
+}
+
+fn ring_point(index: usize, segments: usize) -> Vec2 {
+    Vec2::from_angle(index as f32 / segments as f32 * std::f32::consts::PI * 2.0)
+}
@@ -0,0 +1,156 @@
+use super::*;
+
+const MENU_ZOOM: f32 = 0.02;
+const GAME_ZOOM: f32 = 0.0055;
+const ZOOM_SPEED: f32 = 0.05;
+const TRAUMA_DECAY: f32 = 1.5;
+const SHAKE_OFFSET: f32 = 4.0;
+const SHAKE_ANGLE: f32 = 0.03;
+const ZOOM_PUNCH_MAX: f32 = 0.15;
+const ZOOM_PUNCH_DECAY: f32 = 8.0;
+const FOLLOW_SPEED: f32 = 5.0;
+
+pub struct CameraController {
+    pub position: Vec2,
+    pub zoom: f32,
+    /// Shake amount in 0..1, the actual shake grows with its square
+    pub trauma: f32,
+    /// Extra zoom on top of the current one, decays back to zero
+    pub zoom_punch: f32,
+    /// Half size of the area around the camera center the player can move in freely
+    pub follow: Option<Vec2>,
+    shake: (Vec2, f32),
+}
+
+impl CameraController {
+    pub fn new(follow: Option<Vec2>) -> Self {
+        Self {
+            position: vec2(0.0, 0.0),
+            zoom: MENU_ZOOM,
+            trauma: 0.0,
+            zoom_punch: 0.0,
+            follow,
+            shake: (vec2(0.0, 0.0), 0.0),
+        }
+    }
+
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    pub fn punch(&mut self, amount: f32) {
+        self.zoom_punch = (self.zoom_punch + amount).min(ZOOM_PUNCH_MAX);
+    }
+
+    pub fn update(&mut self, delta_time: f32, state: GameState, model: &Model) {
+        let (target_zoom, in_game) = match state {
+            GameState::Menu | GameState::Settings | GameState::Achievements => (MENU_ZOOM, false),
+            GameState::Pregame | GameState::Game | GameState::Paused => (GAME_ZOOM, true),
+        };
+        let delta = target_zoom - self.zoom;
+        if delta.abs() > 1e-4 {
+            self.zoom += delta.signum() * delta.abs().min(ZOOM_SPEED * delta_time);
+        }
+        self.zoom_punch =
+            (self.zoom_punch - self.zoom_punch * ZOOM_PUNCH_DECAY * delta_time).max(0.0);
+
+        // Menus are laid out around the origin
+        let target = match (in_game, self.follow) {
+            (true, Some(dead_zone)) => {
+                let offset = model.player_body().position - self.position;
+                let outside = offset - offset.clamp(-dead_zone, dead_zone);
+                self.position + outside
+            }
+            _ => vec2(0.0, 0.0),
+        };
+        self.position += (target - self.position) * (FOLLOW_SPEED * delta_time).min(1.0);
+        if in_game {
+            self.position = self.clamp_to(model.bounds, self.position);
+        }
+
+        self.trauma = (self.trauma - TRAUMA_DECAY * delta_time).max(0.0);
+        let shake = self.trauma * self.trauma;
+        self.shake = (
+            vec2(rand::gen_range(-1.0, 1.0), rand::gen_range(-1.0, 1.0)) * shake * SHAKE_OFFSET,
+            rand::gen_range(-1.0, 1.0) * shake * SHAKE_ANGLE,
+        );
+    }
+
+    /// The camera without shake, used to map input to the world
+    pub fn camera(&self) -> Camera2D {
+        let zoom = self.zoom * (1.0 + self.zoom_punch);
+        Camera2D {
+            target: self.position,
+            offset: vec2(0.0, 0.0),
+            zoom: vec2(zoom, zoom * screen_width() / screen_height()),
+            ..Default::default()
+        }
+    }
+
+    pub fn shaken_camera(&self) -> Camera2D {
+        let (offset, angle) = self.shake;
+        let camera = self.camera();
+        Camera2D {
+            target: camera.target + offset,
+            rotation: angle.to_degrees(),
+            ..camera
+        }
+    }
+
+    pub fn view_bounds(&self) -> Bounds {
+        let half_view = self.half_view();
+        Bounds {
+            min: self.position - half_view,
+            max: self.position + half_view,
+        }
+    }
+
+    fn half_view(&self) -> Vec2 {
+        let zoom = self.camera().zoom;
+        vec2(1.0 / zoom.x, 1.0 / zoom.y).abs()
+    }
+
+    /// Keeps the view inside the bounds, or centers it if the bounds are smaller
+    fn clamp_to(&self, bounds: Bounds, position: Vec2) -> Vec2 {
+        let half_view = self.half_view();
+        let min = bounds.min + half_view;
+        let max = bounds.max - half_view;
+        let center = (bounds.min + bounds.max) / 2.0;
+        vec2(
+            if min.x <= max.x {
+                position.x.clamp(min.x, max.x)
+            } else {
+                center.x
+            },
+            if min.y <= max.y {
+                position.y.clamp(min.y, max.y)
+            } else {
+                center.y
+            },
+        )
+    }
+}
+
+impl EventHandler for CameraController {
+    fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::HeadHit { strength, .. } => {
+                self.add_trauma(strength * 0.002);
+                if strength > 150.0 {
+                    self.punch(0.05);
+                }
+            }
+            Event::PlayerDamaged { amount, .. } => self.add_trauma(amount * 0.01),
+            Event::Sound {
+                sound: EventSound::Explosion,
+                intensity,
+                ..
+            } => self.add_trauma(intensity * 0.0005),
+            Event::PlayerDied { .. } => {
+                self.add_trauma(0.8);
+                self.punch(ZOOM_PUNCH_MAX);
+            }
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use super::draw::draw_arc;
+use super::*;
+
+const VELOCITY_SCALE: f32 = 0.2;
+const TARGET_COLOR: Color = Color::new(1.0, 0.2, 0.2, 0.4);
+const VELOCITY_COLOR: Color = Color::new(0.2, 1.0, 0.2, 0.8);
+const COOLDOWN_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.6);
+const HEAD_TARGET_COLOR: Color = Color::new(0.3, 0.6, 1.0, 0.8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DebugLayer {
+    Colliders,
+    Velocities,
+    Targets,
+    Cooldowns,
+    Spawners,
+    HeadTarget,
+    Counts,
+    Profiler,
+}
+
+impl DebugLayer {
+    /// Toggle keys while the debug overlay is on
+    pub const ALL: [(DebugLayer, KeyCode); 8] = [
+        (DebugLayer::Colliders, KeyCode::F1),
+        (DebugLayer::Velocities, KeyCode::F2),
+        (DebugLayer::Targets, KeyCode::F3),
+        (DebugLayer::Cooldowns, KeyCode::F4),
+        (DebugLayer::Spawners, KeyCode::F5),
+        (DebugLayer::HeadTarget, KeyCode::F7),
+        (DebugLayer::Counts, KeyCode::F8),
+        (DebugLayer::Profiler, KeyCode::F9),
+    ];
+
+    pub fn defaults() -> HashSet<DebugLayer> {
+        [
+            DebugLayer::Colliders,
+            DebugLayer::Counts,
+            DebugLayer::Profiler,
+        ]
+        .iter()
+        .copied()
+        .collect()
+    }
+}
+
+impl Renderer {
+    pub(super) fn shows_debug(&self, layer: DebugLayer) -> bool {
+        self.ui_state.debug_mode && self.ui_state.debug_layers.contains(&layer)
+    }
+
+    /// World space layers, drawn on top of the game
+    pub(super) fn draw_debug_world(&self, batch: &mut CircleBatch, model: &Model) {
+        if self.shows_debug(DebugLayer::Velocities) {
+            for (_, rigidbody) in model.store.rigidbodies.iter() {
+                draw_vector(rigidbody.position, rigidbody.velocity * VELOCITY_SCALE);
+            }
+            let head = &model.player.head;
+            draw_vector(head.position, head.velocity * VELOCITY_SCALE);
+        }
+
+        if self.shows_debug(DebugLayer::Targets) {
+            for (id, rigidbody) in model.store.rigidbodies.iter() {
+                let target = match (model.store.ais.get(id), model.store.attacks.get(id)) {
+                    (Some(Ai::Follow { leader, .. }), _) => leader
+                        .and_then(|leader| model.store.rigidbodies.get(leader))
+                        .map(|leader| leader.position),
+                    (Some(Ai::Chase { targets }), _) => model
+                        .find_closest(rigidbody.position, targets)
+                        .map(|(_, position)| position),
+                    (None, Some(attacker)) => model
+                        .find_closest(rigidbody.position, &attacker.targets)
+                        .map(|(_, position)| position),
+                    (None, None) => None,
+                };
+                if let Some(target) = target {
+                    let start = rigidbody.position;
+                    draw_line(start.x, start.y, target.x, target.y, 0.3, TARGET_COLOR);
+                }
+            }
+        }
+
+        if self.shows_debug(DebugLayer::Cooldowns) {
+            for (id, attacker) in model.store.attacks.iter() {
+                let rigidbody = match model.store.rigidbodies.get(id) {
+                    Some(rigidbody) => rigidbody,
+                    None => continue,
+                };
+                let time = &attacker.attack.attack_time;
+                let ready = 1.0 - time.hp_frac();
+                draw_arc(
+                    rigidbody.position,
+                    rigidbody.collider.radius + 1.5,
+                    vec2(0.0, -1.0).rotate(Vec2::from_angle(std::f32::consts::PI * ready)),
+                    std::f32::consts::PI * 2.0 * ready,
+                    COOLDOWN_COLOR,
+                );
+            }
+        }
+
+        if self.shows_debug(DebugLayer::Spawners) {
+            for spawner in &model.spawners {
+                batch.outline(spawner.position, spawner.spawn_group.radius, SPAWNER_COLOR);
+            }
+        }
+
+        if self.shows_debug(DebugLayer::HeadTarget) {
+            let head = model.player.head.position;
+            let target = model.player.head_target;
+            draw_line(head.x, head.y, target.x, target.y, 0.2, HEAD_TARGET_COLOR);
+            batch.outline(target, 1.5, HEAD_TARGET_COLOR);
+        }
+
+        if self.shows_debug(DebugLayer::Colliders) {
+            for (id, rigidbody) in model.store.rigidbodies.iter() {
+                let color = model
+                    .store
+                    .appearances
+                    .get(id)
+                    .map_or(WHITE, |appearance| appearance.color);
+                self.draw_rigidbody(batch, rigidbody, color);
+                let health_frac = model.store.health_frac(id);
+                batch.circle(
+                    rigidbody.position,
+                    health_frac * rigidbody.collider.radius,
+                    color,
+                );
+            }
+            self.draw_rigidbody(batch, &model.player.head, PLAYER_COLOR);
+        }
+        batch.flush();
+    }
+
+    /// Screen space layers
+    pub(super) fn draw_debug_ui(&self, model: &Model) {
+        if self.shows_debug(DebugLayer::Spawners) {
+            for spawner in &model.spawners {
+                let position = self.view_camera.world_to_screen(spawner.position);
+                draw_text(
+                    &format!("{:.1}s", spawner.time_left),
+                    position.x,
+                    position.y,
+                    20.0,
+                    SPAWNER_COLOR,
+                );
+            }
+        }
+
+        if self.shows_debug(DebugLayer::Counts) {
+            let enemies = model
+                .store
+                .factions
+                .iter()
+                .filter(|&(_, &faction)| faction == EntityType::Enemy)
+                .count();
+            let lines = [
+                format!("entities: {}", model.store.rigidbodies.iter().count()),
+                format!("enemies: {}", enemies),
+                format!("particles: {}", model.particles.len()),
+                format!("emitters: {}", model.emitters.len()),
+            ];
+            for (index, line) in lines.iter().enumerate() {
+                draw_text(
+                    line,
+                    screen_width() - 200.0,
+                    screen_height() - 20.0 - index as f32 * 20.0,
+                    20.0,
+                    WHITE,
+                );
+            }
+        }
+
+        if self.shows_debug(DebugLayer::Profiler) {
+            draw_profiler();
+        }
+    }
+}
+
+fn draw_vector(origin: Vec2, vector: Vec2) {
+    let end = origin + vector;
+    draw_line(origin.x, origin.y, end.x, end.y, 0.3, VELOCITY_COLOR);
+}
+
+/// Average timings and a graph of recent samples for every profiler scope
+fn draw_profiler() {
+    const LEFT: f32 = 10.0;
+    const TOP: f32 = 50.0;
+    const ROW_HEIGHT: f32 = 24.0;
+    const LABEL_WIDTH: f32 = 220.0;
+    const GRAPH_HEIGHT: f32 = 18.0;
+    /// Samples at this duration fill the whole graph height
+    const GRAPH_MAX_MS: f32 = 4.0;
+
+    profiler::with(|profiler| {
+        let width = profiler::HISTORY_LENGTH as f32;
+        draw_rectangle(
+            LEFT - 5.0,
+            TOP - 5.0,
+            LABEL_WIDTH + width + 10.0,
+            profiler.scopes.len() as f32 * ROW_HEIGHT + 10.0,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        for (row, scope) in profiler.scopes.iter().enumerate() {
+            let y = TOP + row as f32 * ROW_HEIGHT;
+            draw_text(
+                &format!("{}: {:.2}ms", scope.name, scope.average()),
+                LEFT,
+                y + GRAPH_HEIGHT - 4.0,
+                20.0,
+                WHITE,
+            );
+            let graph_left = LEFT + LABEL_WIDTH;
+            draw_rectangle_lines(graph_left, y, width, GRAPH_HEIGHT, 1.0, DARKGRAY);
+            for (index, &millis) in scope.history.iter().enumerate() {
+                let height = (millis / GRAPH_MAX_MS).min(1.0) * GRAPH_HEIGHT;
+                let color = if millis > GRAPH_MAX_MS { RED } else { GREEN };
+                draw_line(
+                    graph_left + index as f32,
+                    y + GRAPH_HEIGHT,
+                    graph_left + index as f32,
+                    y + GRAPH_HEIGHT - height,
+                    1.0,
+                    color,
+                );
+            }
+        }
+    });
+}
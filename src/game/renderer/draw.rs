@@ -1,28 +1,31 @@
 use super::*;
 
 impl Renderer {
-    pub fn draw(&self, model: &Model) {
+    pub fn draw(&mut self, model: &Model, alpha: f32) {
+        self.alpha = alpha;
         clear_background(BACKGROUND_COLOR);
         self.draw_game(model);
-        self.draw_ui();
+        self.draw_ui(model);
     }
 
     fn draw_game(&self, model: &Model) {
-        set_camera(&self.game_camera);
+        set_camera(&self.view_camera);
+        let mut batch = CircleBatch::new(&self.view_camera);
 
         // Area effects
         for area_effect in &model.area_effects {
             let area_color = match &area_effect.effect {
                 Effect::Heal { .. } => Color::new(0.0, 1.0, 0.0, 0.5),
             };
-            draw_circle(area_effect.position, area_effect.radius, area_color);
+            batch.circle(area_effect.position, area_effect.radius, area_color);
         }
 
         // Player health
-        let coefficient = model.player.entity.health.hp_frac();
+        let coefficient = model.player_health().hp_frac();
         let player_life_color = color_alpha(PLAYER_LIFE_COLOR, 0.5);
-        draw_circle(
-            model.player.entity.rigidbody.position,
+        let player_position = self.position(model.player_body());
+        batch.circle(
+            player_position,
             model.player.chain_length * coefficient,
             player_life_color,
         );
@@ -30,8 +33,8 @@ impl Renderer {
         // Spawners
         let spawner_color = Color::new(SPAWNER_COLOR.r, SPAWNER_COLOR.g, SPAWNER_COLOR.b, 0.5);
         for spawner in &model.spawners {
-            draw_circle_outline(spawner.position, spawner.spawn_group.radius, spawner_color);
-            draw_circle(
+            batch.outline(spawner.position, spawner.spawn_group.radius, spawner_color);
+            batch.circle(
                 spawner.position,
                 spawner.time_left / spawner.time_left_max * spawner.spawn_group.radius,
                 spawner_color,
@@ -40,30 +43,83 @@ impl Renderer {
 
         // Particles
         for particle in &model.particles {
-            self.draw_rigidbody(&particle.rigidbody, particle.color);
+            self.draw_rigidbody(&mut batch, &particle.rigidbody, particle.color);
         }
 
+        batch.flush();
+
         // Other entities
-        for enemy in model.entities() {
-            self.draw_rigidbody(&enemy.entity().rigidbody, enemy.entity().color);
-            let health_frac = enemy.health_frac();
-            draw_circle(
-                enemy.entity().rigidbody.position,
-                health_frac * enemy.entity().rigidbody.collider.radius,
-                enemy.entity().color,
+        for (id, rigidbody) in model.store.rigidbodies.iter() {
+            if id == model.player.id {
+                continue;
+            }
+            let color = match model.store.appearances.get(id) {
+                Some(appearance) => appearance.color,
+                None => continue,
+            };
+            self.draw_sprite(&mut batch, model, id, rigidbody, color);
+        }
+
+        batch.flush();
+
+        // Attack indicators
+        for (id, attacker) in model.store.attacks.iter() {
+            if let Some(telegraph) = &attacker.attack.telegraph {
+                if let (Some(wind_up), Some(progress)) = (telegraph.state, telegraph.progress()) {
+                    let position = self.position(model.store.rigidbodies.get(id).unwrap());
+                    draw_telegraph(
+                        &mut batch,
+                        position,
+                        wind_up.direction,
+                        telegraph.shape,
+                        progress,
+                    );
+                }
+            }
+        }
+        for beam in &self.beams {
+            let alpha = beam.time_left / BEAM_FLASH_TIME;
+            draw_line(
+                beam.start.x,
+                beam.start.y,
+                beam.end.x,
+                beam.end.y,
+                1.0,
+                color_alpha(BEAM_COLOR, alpha),
             );
         }
+        for (id, shield) in model.store.shields.iter() {
+            let rigidbody = model.store.rigidbodies.get(id).unwrap();
+            draw_arc(
+                self.position(rigidbody),
+                rigidbody.collider.radius + shield.radius,
+                shield.direction,
+                shield.arc,
+                SHIELD_COLOR,
+            );
+        }
+
+        batch.flush();
 
         // Player border
-        draw_circle_outline(
-            model.player.entity.rigidbody.position,
+        batch.outline(
+            player_position,
             model.player.chain_length,
             PLAYER_BORDER_COLOR,
         );
 
         // Player body & head
-        self.draw_rigidbody(&model.player.entity.rigidbody, PLAYER_COLOR);
-        self.draw_rigidbody(&model.player.head, PLAYER_COLOR);
+        let head = &model.player.head;
+        self.draw_sprite(
+            &mut batch,
+            model,
+            model.player.id,
+            model.player_body(),
+            PLAYER_COLOR,
+        );
+        batch.circle(self.position(head), head.collider.radius, PLAYER_COLOR);
+
+        batch.flush();
 
         // Bounds
         let bounds_size = model.bounds.max - model.bounds.min;
@@ -75,15 +131,28 @@ impl Renderer {
             0.5,
             BORDER_COLOR,
         );
+
+        self.draw_debug_world(&mut batch, model);
+    }
+
+    pub(super) fn draw_rigidbody(
+        &self,
+        batch: &mut CircleBatch,
+        rigidbody: &RigidBody,
+        color: Color,
+    ) {
+        batch.outline(self.position(rigidbody), rigidbody.collider.radius, color);
     }
 
-    fn draw_rigidbody(&self, rigidbody: &RigidBody, color: Color) {
-        draw_circle_outline(rigidbody.position, rigidbody.collider.radius, color);
+    /// Where to draw a body between the last two fixed ticks
+    pub(super) fn position(&self, rigidbody: &RigidBody) -> Vec2 {
+        rigidbody.interpolated_position(self.alpha)
     }
 
-    fn draw_ui(&self) {
+    fn draw_ui(&self, model: &Model) {
         set_default_camera();
         self.ui_state.draw();
+        self.draw_debug_ui(model);
     }
 }
 
@@ -91,10 +160,54 @@ fn color_alpha(color: Color, alpha: f32) -> Color {
     Color::new(color.r, color.g, color.b, alpha)
 }
 
-fn draw_circle(position: Vec2, radius: f32, color: Color) {
-    draw_poly(position.x, position.y, 200, radius, 0.0, color);
+fn draw_telegraph(
+    batch: &mut CircleBatch,
+    position: Vec2,
+    direction: Vec2,
+    shape: TelegraphShape,
+    progress: f32,
+) {
+    let color = color_alpha(TELEGRAPH_COLOR, 0.2 + progress * 0.4);
+    match shape {
+        TelegraphShape::Line { length, width } => {
+            let end = position + direction * length;
+            draw_line(
+                position.x,
+                position.y,
+                end.x,
+                end.y,
+                width * progress,
+                color,
+            );
+        }
+        TelegraphShape::Cone { length, angle } => {
+            for &side in &[-angle / 2.0, angle / 2.0] {
+                let end = position + direction.rotate(Vec2::from_angle(side)) * length;
+                draw_line(position.x, position.y, end.x, end.y, 0.5, color);
+            }
+            draw_arc(position, length, direction, angle, color);
+            draw_arc(position, length * progress, direction, angle, color);
+        }
+        TelegraphShape::Circle { radius } => {
+            batch.outline(position, radius, color);
+            batch.circle(
+                position,
+                radius * progress,
+                color_alpha(TELEGRAPH_COLOR, 0.1 + progress * 0.2),
+            );
+        }
+    }
 }
 
-fn draw_circle_outline(position: Vec2, radius: f32, color: Color) {
-    draw_poly_lines(position.x, position.y, 50, radius, 0.0, 0.2, color);
+pub(super) fn draw_arc(center: Vec2, radius: f32, direction: Vec2, arc: f32, color: Color) {
+    const SEGMENTS: usize = 12;
+    let start = direction.rotate(Vec2::from_angle(-arc / 2.0));
+    let step = Vec2::from_angle(arc / SEGMENTS as f32);
+    let mut point = start;
+    for _ in 0..SEGMENTS {
+        let next = point.rotate(step);
+        let (a, b) = (center + point * radius, center + next * radius);
+        draw_line(a.x, a.y, b.x, b.y, 0.5, color);
+        point = next;
+    }
 }
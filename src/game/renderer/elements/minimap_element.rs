@@ -0,0 +1,82 @@
+use super::*;
+
+const MARKER_SIZE: f32 = 2.0;
+
+pub struct MinimapElement {
+    pub size: Vec2,
+    pub ui_object: UIObject,
+    markers: Vec<(Vec2, Color)>,
+    view: Option<(Vec2, Vec2)>,
+}
+
+impl MinimapElement {
+    pub fn new(size: Vec2, ui_object: UIObject) -> Self {
+        Self {
+            size,
+            ui_object,
+            markers: vec![],
+            view: None,
+        }
+    }
+
+    /// Collects marker positions relative to the arena, from 0 to 1 with y pointing down
+    pub fn update_markers(&mut self, model: &Model, view: Bounds) {
+        let bounds = model.bounds;
+        let size = bounds.max - bounds.min;
+        // The world's y axis points up on screen, the minimap's points down
+        let relative = |position: Vec2| {
+            let relative = (position - bounds.min) / size;
+            vec2(relative.x, 1.0 - relative.y)
+        };
+
+        self.markers.clear();
+        for area_effect in &model.area_effects {
+            self.markers
+                .push((relative(area_effect.position), HEAL_ZONE_COLOR));
+        }
+        for spawner in &model.spawners {
+            self.markers
+                .push((relative(spawner.position), SPAWNER_COLOR));
+        }
+        for (id, &faction) in model.store.factions.iter() {
+            if faction != EntityType::Enemy || !model.store.is_alive(id) {
+                continue;
+            }
+            if let Some(rigidbody) = model.store.rigidbodies.get(id) {
+                self.markers
+                    .push((relative(rigidbody.position), ENEMY_MARKER_COLOR));
+            }
+        }
+        self.markers
+            .push((relative(model.player_body().position), PLAYER_COLOR));
+        let (min, max) = (relative(view.min), relative(view.max));
+        self.view = Some((
+            vec2(min.x, max.y).clamp(Vec2::ZERO, Vec2::ONE),
+            vec2(max.x, min.y).clamp(Vec2::ZERO, Vec2::ONE),
+        ));
+    }
+
+    pub fn draw(&self, ui_scale: Vec2, camera_scale: Vec2) {
+        let scale = self.ui_object.scale(ui_scale, camera_scale);
+        let size = self.size * scale;
+        // The object's position is the top right corner
+        let top_left = self.ui_object.global_position(ui_scale, camera_scale) - vec2(size.x, 0.0);
+        draw_rectangle(
+            top_left.x,
+            top_left.y,
+            size.x,
+            size.y,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        draw_rectangle_lines(top_left.x, top_left.y, size.x, size.y, 2.0, BORDER_COLOR);
+        if let Some((min, max)) = self.view {
+            let (min, max) = (top_left + min * size, top_left + max * size);
+            draw_rectangle_lines(min.x, min.y, max.x - min.x, max.y - min.y, 1.0, GRAY);
+        }
+        let marker_size = MARKER_SIZE * scale.min_element();
+        for &(position, color) in &self.markers {
+            let position = top_left + position * size;
+            draw_circle(position.x, position.y, marker_size, color);
+        }
+    }
+}
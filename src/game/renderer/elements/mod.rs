@@ -1,11 +1,15 @@
 use super::*;
 
-mod fps_element;
 mod button_element;
+mod fps_element;
+mod minimap_element;
+mod slider_element;
 mod text_element;
 mod texture_element;
 
 pub use button_element::*;
 pub use fps_element::*;
+pub use minimap_element::*;
+pub use slider_element::*;
 pub use text_element::*;
 pub use texture_element::*;
@@ -0,0 +1,65 @@
+use super::*;
+
+const BAR_WIDTH: f32 = 12.0;
+const BAR_HEIGHT: f32 = 0.8;
+const BAR_OFFSET: f32 = 2.5;
+
+pub struct SliderElement {
+    pub button: ButtonElement,
+    pub label: String,
+    pub value: f32,
+    step: f32,
+}
+
+impl SliderElement {
+    pub fn new(label: String, step: f32, button: ButtonElement) -> Self {
+        Self {
+            button,
+            label,
+            value: 0.0,
+            step,
+        }
+    }
+
+    pub fn point_inside(&self, point: Vec2, ui_scale: Vec2, camera_scale: Vec2) -> bool {
+        self.button.point_inside(point, ui_scale, camera_scale)
+    }
+
+    pub fn next_value(&self) -> f32 {
+        if self.value >= 1.0 - self.step / 2.0 {
+            0.0
+        } else {
+            (self.value + self.step).min(1.0)
+        }
+    }
+
+    pub fn scroll_value(&self, scroll: f32) -> f32 {
+        (self.value + scroll.signum() * self.step).clamp(0.0, 1.0)
+    }
+}
+
+impl UIElement for SliderElement {
+    fn ui_element(&self) -> (&UIObject, UIContent) {
+        self.button.ui_element()
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.button.update(delta_time);
+        self.button.text_element.text = format!("{} {:.0}%", self.label, self.value * 100.0);
+    }
+
+    fn draw(&self, ui_scale: Vec2, camera_scale: Vec2) {
+        self.button.draw(ui_scale, camera_scale);
+
+        let ui_object = &self.button.text_element.ui_object;
+        let scale = ui_object.scale(ui_scale, camera_scale);
+        let position = ui_object.global_position(ui_scale, camera_scale);
+        let width = BAR_WIDTH * scale.x;
+        let height = BAR_HEIGHT * scale.y;
+        let x = position.x - width / 2.0;
+        let y = position.y + BAR_OFFSET * scale.y;
+        let color = self.button.text_element.color;
+        draw_rectangle(x, y, width * self.value, height, color);
+        draw_rectangle_lines(x, y, width, height, 1.0, color);
+    }
+}
@@ -1,35 +1,63 @@
 use super::*;
+use std::collections::VecDeque;
 
+mod batch;
+mod camera;
+mod debug;
 mod draw;
 mod elements;
+mod sprites;
 mod ui_state;
 
+use batch::*;
+use camera::*;
+use debug::*;
 use elements::*;
+use sprites::*;
 use ui_state::*;
 
 const STAGE_SHOW_TIME: f32 = 2.0;
 const DEFAULT_WIDTH: f32 = 800.0;
 const DEFAULT_HEIGHT: f32 = 600.0;
+const BEAM_FLASH_TIME: f32 = 0.15;
+const CAMERA_DEAD_ZONE: Vec2 = Vec2::new(40.0, 20.0);
 
 pub struct Renderer {
     pub game_camera: Camera2D,
-    camera_zoom: f32,
-    camera_zoom_speed: f32,
+    view_camera: Camera2D,
+    pub camera: CameraController,
     ui_state: UIState,
+    beams: Vec<BeamFlash>,
+    sprites: SpriteAtlas,
+    /// Drives looping animations
+    time: f32,
+    /// Interpolation between the last two fixed ticks for the frame being drawn
+    alpha: f32,
+}
+
+struct BeamFlash {
+    start: Vec2,
+    end: Vec2,
+    time_left: f32,
 }
 
 impl Renderer {
     pub fn new(assets: &Rc<Assets>) -> Self {
         Self {
             game_camera: Camera2D::default(),
-            camera_zoom: 0.02,
-            camera_zoom_speed: 0.05,
+            view_camera: Camera2D::default(),
+            camera: CameraController::new(Some(CAMERA_DEAD_ZONE)),
             ui_state: UIState {
                 state: GameState::Menu,
                 ui_scale: vec2(1.0, 1.0),
                 camera_scale: vec2(1.0, 1.0),
                 debug_mode: false,
+                debug_layers: DebugLayer::defaults(),
+                show_fps: false,
                 player_alive: true,
+                can_continue: false,
+                mouse_control: false,
+                ui_input: true,
                 stage: 0,
                 stage_timer: 0.0,
                 play_button: ButtonElement::new(
@@ -58,6 +86,76 @@ impl Renderer {
                         UIObject::new(vec2(0.5, 0.5), vec2(0.0, 20.0), UIScaleMode::World),
                     ),
                 ),
+                continue_button: ButtonElement::new(
+                    5.0,
+                    10.0,
+                    20.0,
+                    4.0,
+                    8.0,
+                    TextElement::new(
+                        "CONTINUE".to_owned(),
+                        50.0,
+                        WHITE,
+                        UIObject::new(vec2(0.5, 0.5), vec2(14.0, -14.0), UIScaleMode::World),
+                    ),
+                ),
+                settings_button: ButtonElement::new(
+                    5.0,
+                    10.0,
+                    20.0,
+                    4.0,
+                    8.0,
+                    TextElement::new(
+                        "SETTINGS".to_owned(),
+                        50.0,
+                        WHITE,
+                        UIObject::new(vec2(0.5, 0.5), vec2(20.0, 0.0), UIScaleMode::World),
+                    ),
+                ),
+                achievements_button: ButtonElement::new(
+                    5.0,
+                    10.0,
+                    20.0,
+                    3.0,
+                    6.0,
+                    TextElement::new(
+                        "ACHIEVEMENTS".to_owned(),
+                        50.0,
+                        WHITE,
+                        UIObject::new(vec2(0.5, 0.5), vec2(-20.0, 0.0), UIScaleMode::World),
+                    ),
+                ),
+                paused_element: TextElement::new(
+                    "PAUSED".to_owned(),
+                    50.0,
+                    WHITE,
+                    UIObject::new(vec2(0.5, 0.5), vec2(0.0, -150.0), UIScaleMode::KeepRatio),
+                ),
+                resume_button: pause_button("RESUME", 0),
+                pause_settings_button: pause_button("SETTINGS", 1),
+                menu_button: pause_button("QUIT TO MENU", 2),
+                back_button: settings_button("BACK", 0),
+                master_volume_slider: SliderElement::new(
+                    "MASTER".to_owned(),
+                    0.1,
+                    settings_button("", 1),
+                ),
+                music_volume_slider: SliderElement::new(
+                    "MUSIC".to_owned(),
+                    0.1,
+                    settings_button("", 2),
+                ),
+                sfx_volume_slider: SliderElement::new(
+                    "SFX".to_owned(),
+                    0.1,
+                    settings_button("", 3),
+                ),
+                mute_button: settings_button("", 4),
+                fullscreen_button: settings_button("", 5),
+                head_control_button: settings_button("", 6),
+                show_fps_button: settings_button("", 7),
+                difficulty_button: settings_button("", 8),
+                dynamic_difficulty_button: settings_button("", 9),
                 tutorial_texture: TextureElement::new(
                     assets.tutorial,
                     WHITE,
@@ -79,19 +177,56 @@ impl Renderer {
                     WHITE,
                     UIObject::new(vec2(0.5, 0.0), vec2(0.0, 100.0), UIScaleMode::KeepRatio),
                 ),
+                mutator_hint_element: TextElement::new(
+                    "1-6: PICK MUTATORS   0: ROLL EVERY 5 STAGES".to_owned(),
+                    20.0,
+                    GRAY,
+                    UIObject::new(vec2(0.5, 1.0), vec2(0.0, -60.0), UIScaleMode::KeepRatio),
+                ),
+                mutators_element: TextElement::new(
+                    "".to_owned(),
+                    20.0,
+                    WHITE,
+                    UIObject::new(vec2(0.5, 1.0), vec2(0.0, -30.0), UIScaleMode::KeepRatio),
+                ),
                 death_element: TextElement::new(
                     "YOU DIED".to_owned(),
                     50.0,
                     WHITE,
-                    UIObject::new(vec2(0.5, 0.5), vec2(0.0, 0.0), UIScaleMode::KeepRatio),
+                    UIObject::new(vec2(0.5, 0.5), vec2(0.0, -130.0), UIScaleMode::KeepRatio),
                 ),
                 reset_element: TextElement::new(
                     "PRESS R TO RESET".to_owned(),
                     50.0,
                     WHITE,
-                    UIObject::new(vec2(0.5, 0.5), vec2(0.0, 50.0), UIScaleMode::KeepRatio),
+                    UIObject::new(vec2(0.5, 0.5), vec2(0.0, 215.0), UIScaleMode::KeepRatio),
+                ),
+                export_element: TextElement::new(
+                    "PRESS E TO EXPORT STATS".to_owned(),
+                    25.0,
+                    WHITE,
+                    UIObject::new(vec2(0.5, 0.5), vec2(0.0, 250.0), UIScaleMode::KeepRatio),
                 ),
+                mutator_picks: String::new(),
+                summary_elements: vec![],
+                achievement_elements: vec![],
+                toast_element: TextElement::new(
+                    "".to_owned(),
+                    30.0,
+                    YELLOW,
+                    UIObject::new(vec2(0.5, 0.0), vec2(0.0, 40.0), UIScaleMode::KeepRatio),
+                ),
+                minimap: MinimapElement::new(
+                    vec2(160.0, 90.0),
+                    UIObject::new(vec2(1.0, 0.0), vec2(-10.0, 10.0), UIScaleMode::KeepRatio),
+                ),
+                toasts: VecDeque::new(),
+                toast_timer: 0.0,
             },
+            beams: vec![],
+            sprites: SpriteAtlas::new(assets.sprites),
+            time: 0.0,
+            alpha: 1.0,
         }
     }
 
@@ -100,27 +235,36 @@ impl Renderer {
         delta_time: f32,
         state: GameState,
         model: &Model,
+        settings: &Settings,
     ) -> Option<GameUpdate> {
-        let target_zoom = match state {
-            GameState::Menu => 0.02,
-            GameState::Pregame | GameState::Game => 0.0055,
-        };
-        let delta = target_zoom - self.camera_zoom;
-        if delta.abs() > 1e-4 {
-            self.camera_zoom +=
-                delta.signum() * delta.abs().min(self.camera_zoom_speed * delta_time);
+        self.camera.update(delta_time, state, model);
+        self.game_camera = self.camera.camera();
+        self.view_camera = self.camera.shaken_camera();
+        self.ui_state
+            .minimap
+            .update_markers(model, self.camera.view_bounds());
+
+        self.time += delta_time;
+        for beam in &mut self.beams {
+            beam.time_left -= delta_time;
         }
-        self.game_camera = Camera2D {
-            offset: vec2(0.0, 0.0),
-            zoom: vec2(
-                self.camera_zoom,
-                self.camera_zoom * screen_width() / screen_height(),
-            ),
-            ..Default::default()
-        };
+        self.beams.retain(|beam| beam.time_left > 0.0);
 
         self.ui_state.state = state;
-        self.ui_state.player_alive = model.player.entity.health.is_alive();
+        self.ui_state.player_alive = model.player_alive();
+        if !self.ui_state.player_alive {
+            self.ui_state.set_summary(&model.stats);
+        }
+        self.ui_state.apply_settings(settings);
+        let names: Vec<&str> = model
+            .mutators
+            .iter()
+            .map(|mutator| mutator.name())
+            .collect();
+        self.ui_state.mutators_element.text = match state {
+            GameState::Pregame => self.ui_state.mutator_picks.clone(),
+            _ => mutators_text(&names),
+        };
 
         self.ui_state.update(
             self.game_camera.zoom.xx() / 2.0,
@@ -129,8 +273,119 @@ impl Renderer {
         )
     }
 
+    pub fn set_can_continue(&mut self, can_continue: bool) {
+        self.ui_state.can_continue = can_continue;
+    }
+
+    /// Menus are pointed at with the mouse instead of the head while the model is frozen
+    pub fn set_mouse_control(&mut self, mouse_control: bool) {
+        self.ui_state.mouse_control = mouse_control;
+    }
+
+    pub fn set_ui_input(&mut self, ui_input: bool) {
+        self.ui_state.ui_input = ui_input;
+    }
+
+    pub fn set_mutator_picks(&mut self, picks: &[Mutator], roll: bool) {
+        let mut names: Vec<&str> = Mutator::ALL
+            .iter()
+            .enumerate()
+            .filter(|(_, mutator)| picks.contains(mutator))
+            .map(|(_, mutator)| mutator.name())
+            .collect();
+        if roll {
+            names.push("ROLLING");
+        }
+        self.ui_state.mutator_picks = mutators_text(&names);
+    }
+
+    pub fn debug_mode(&self) -> bool {
+        self.ui_state.debug_mode
+    }
+
+    pub fn show_toast(&mut self, text: String) {
+        self.ui_state.toasts.push_back(text);
+    }
+
+    pub fn show_achievements(&mut self, achievements: &Achievements) {
+        self.ui_state.set_achievements(achievements);
+    }
+
     pub fn next_wave(&mut self, stage: usize) {
         self.ui_state.stage = stage;
         self.ui_state.stage_timer = STAGE_SHOW_TIME;
     }
 }
+
+impl EventHandler for Renderer {
+    fn handle_event(&mut self, event: &Event) {
+        self.camera.handle_event(event);
+        match *event {
+            Event::NextWave { stage } => self.next_wave(stage),
+            Event::BeamFired { start, end } => self.beams.push(BeamFlash {
+                start,
+                end,
+                time_left: BEAM_FLASH_TIME,
+            }),
+            Event::MutatorAdded { mutator } => {
+                self.show_toast(format!("MUTATOR: {}", mutator.name()))
+            }
+            _ => (),
+        }
+    }
+}
+
+fn mutators_text(names: &[&str]) -> String {
+    if names.is_empty() {
+        String::new()
+    } else {
+        format!("MUTATORS: {}", names.join(", "))
+    }
+}
+
+const PAUSE_BUTTON_SPACING: f32 = 80.0;
+
+fn pause_button(text: &str, index: usize) -> ButtonElement {
+    ButtonElement::new(
+        50.0,
+        70.0,
+        100.0,
+        30.0,
+        45.0,
+        TextElement::new(
+            text.to_owned(),
+            50.0,
+            WHITE,
+            UIObject::new(
+                vec2(0.5, 0.5),
+                vec2(0.0, (index as f32 - 1.0) * PAUSE_BUTTON_SPACING),
+                UIScaleMode::KeepRatio,
+            ),
+        ),
+    )
+}
+
+const SETTINGS_ITEMS: usize = 10;
+const SETTINGS_RADIUS: f32 = 20.0;
+
+fn settings_button(text: &str, index: usize) -> ButtonElement {
+    let angle = index as f32 / SETTINGS_ITEMS as f32 * std::f32::consts::PI * 2.0;
+    let (sin, cos) = angle.sin_cos();
+    ButtonElement::new(
+        4.0,
+        7.0,
+        20.0,
+        3.0,
+        5.0,
+        TextElement::new(
+            text.to_owned(),
+            50.0,
+            WHITE,
+            UIObject::new(
+                vec2(0.5, 0.5),
+                vec2(sin, -cos) * SETTINGS_RADIUS,
+                UIScaleMode::World,
+            ),
+        ),
+    )
+}
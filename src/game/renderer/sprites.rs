@@ -0,0 +1,224 @@
+use super::*;
+
+const CELL_SIZE: f32 = 16.0;
+/// How much of a cell the body radius takes up
+const BODY_RADIUS: f32 = 6.0;
+const MOVE_THRESHOLD: f32 = 5.0;
+/// How long the attack animation keeps playing after the attack fired
+const ATTACK_SHOW_TIME: f32 = 0.3;
+const MIN_HEALTH_TINT: f32 = 0.35;
+
+pub struct SpriteAtlas {
+    texture: Texture2D,
+}
+
+#[derive(Clone, Copy)]
+pub struct Animation {
+    pub row: usize,
+    pub frames: usize,
+    pub fps: f32,
+}
+
+impl Animation {
+    const fn new(row: usize, frames: usize, fps: f32) -> Self {
+        Self { row, frames, fps }
+    }
+
+    /// Loops over `time`, or plays once over `progress` for death animations
+    pub fn frame(&self, time: f32, progress: Option<f32>) -> usize {
+        match progress {
+            Some(progress) => ((progress * self.frames as f32) as usize).min(self.frames - 1),
+            None => (time * self.fps) as usize % self.frames,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationKind {
+    Idle,
+    Move,
+    Attack,
+    Death,
+}
+
+#[derive(Clone, Copy)]
+pub struct SpriteDefinition {
+    pub idle: Animation,
+    pub moving: Animation,
+    pub attack: Animation,
+    pub death: Animation,
+    /// Whether the sprite turns to face its velocity
+    pub rotate: bool,
+}
+
+impl SpriteDefinition {
+    pub fn animation(&self, kind: AnimationKind) -> Animation {
+        match kind {
+            AnimationKind::Idle => self.idle,
+            AnimationKind::Move => self.moving,
+            AnimationKind::Attack => self.attack,
+            AnimationKind::Death => self.death,
+        }
+    }
+}
+
+const CREATURE: SpriteDefinition = SpriteDefinition {
+    idle: Animation::new(0, 2, 2.0),
+    moving: Animation::new(1, 4, 8.0),
+    attack: Animation::new(2, 2, 10.0),
+    death: Animation::new(3, 4, 0.0),
+    rotate: true,
+};
+
+const PROJECTILE: SpriteDefinition = SpriteDefinition {
+    idle: Animation::new(4, 2, 12.0),
+    moving: Animation::new(4, 2, 12.0),
+    attack: Animation::new(4, 2, 12.0),
+    death: Animation::new(4, 2, 12.0),
+    rotate: true,
+};
+
+const MISSILE: SpriteDefinition = SpriteDefinition {
+    idle: Animation::new(5, 2, 12.0),
+    moving: Animation::new(5, 2, 12.0),
+    attack: Animation::new(5, 2, 12.0),
+    death: Animation::new(5, 2, 12.0),
+    rotate: true,
+};
+
+/// Entities without a definition are drawn as circles
+pub fn sprite_definition(kind: EntityKind) -> Option<SpriteDefinition> {
+    match kind {
+        EntityKind::Player | EntityKind::Enemy(_) | EntityKind::Minion | EntityKind::Corpse => {
+            Some(CREATURE)
+        }
+        EntityKind::Projectile | EntityKind::Bomb => Some(PROJECTILE),
+        EntityKind::Missile => Some(MISSILE),
+        EntityKind::Explosion => None,
+    }
+}
+
+impl SpriteAtlas {
+    pub fn new(texture: Texture2D) -> Self {
+        texture.set_filter(FilterMode::Nearest);
+        Self { texture }
+    }
+
+    pub fn draw(
+        &self,
+        animation: Animation,
+        frame: usize,
+        position: Vec2,
+        rigidbody: &RigidBody,
+        rotate: bool,
+        tint: Color,
+    ) {
+        let size = rigidbody.collider.radius * CELL_SIZE / BODY_RADIUS;
+        let position = position - vec2(size, size) / 2.0;
+        let rotation = if rotate && rigidbody.velocity.length() > MOVE_THRESHOLD {
+            rigidbody.velocity.y.atan2(rigidbody.velocity.x)
+        } else {
+            0.0
+        };
+        draw_texture_ex(
+            self.texture,
+            position.x,
+            position.y,
+            tint,
+            DrawTextureParams {
+                dest_size: Some(vec2(size, size)),
+                source: Some(Rect::new(
+                    frame as f32 * CELL_SIZE,
+                    animation.row as f32 * CELL_SIZE,
+                    CELL_SIZE,
+                    CELL_SIZE,
+                )),
+                rotation,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+impl Renderer {
+    pub(super) fn draw_sprite(
+        &self,
+        batch: &mut CircleBatch,
+        model: &Model,
+        id: EntityId,
+        rigidbody: &RigidBody,
+        color: Color,
+    ) {
+        let kind = match model.store.kinds.get(id) {
+            Some(&kind) => kind,
+            None => return,
+        };
+        let definition = match sprite_definition(kind) {
+            Some(definition) => definition,
+            None => {
+                self.draw_rigidbody(batch, rigidbody, color);
+                return;
+            }
+        };
+        let animation = animation_kind(model, id, kind, rigidbody);
+        let progress = match animation {
+            AnimationKind::Death => model
+                .store
+                .lifetimes
+                .get(id)
+                .map(|lifetime| 1.0 - lifetime.time.hp_frac()),
+            _ => None,
+        };
+        let tint = match model.store.healths.get(id) {
+            Some(health) => health_tint(color, health.hp_frac()),
+            None => color,
+        };
+        let animation = definition.animation(animation);
+        self.sprites.draw(
+            animation,
+            animation.frame(self.time, progress),
+            self.position(rigidbody),
+            rigidbody,
+            definition.rotate,
+            tint,
+        );
+    }
+}
+
+fn animation_kind(
+    model: &Model,
+    id: EntityId,
+    kind: EntityKind,
+    rigidbody: &RigidBody,
+) -> AnimationKind {
+    if kind == EntityKind::Corpse {
+        return AnimationKind::Death;
+    }
+    if let Some(attacker) = model.store.attacks.get(id) {
+        let attack = &attacker.attack;
+        let winding_up = attack
+            .telegraph
+            .as_ref()
+            .is_some_and(|telegraph| telegraph.state.is_some());
+        let just_fired = attack.attack_time.hp_max - attack.attack_time.hp < ATTACK_SHOW_TIME;
+        if winding_up || just_fired {
+            return AnimationKind::Attack;
+        }
+    }
+    if rigidbody.velocity.length() > MOVE_THRESHOLD {
+        AnimationKind::Move
+    } else {
+        AnimationKind::Idle
+    }
+}
+
+/// Darkens the color as the entity loses health
+fn health_tint(color: Color, health_frac: f32) -> Color {
+    let brightness = MIN_HEALTH_TINT + (1.0 - MIN_HEALTH_TINT) * health_frac;
+    Color::new(
+        color.r * brightness,
+        color.g * brightness,
+        color.b * brightness,
+        color.a,
+    )
+}
@@ -1,23 +1,167 @@
 use super::*;
 
+const SUMMARY_FONT_SIZE: f32 = 25.0;
+const SUMMARY_TOP: f32 = -90.0;
+const ACHIEVEMENTS_FONT_SIZE: f32 = 18.0;
+const ACHIEVEMENTS_TOP: f32 = -130.0;
+const TOAST_SHOW_TIME: f32 = 3.0;
+
 pub struct UIState {
     pub state: GameState,
     pub ui_scale: Vec2,
     pub camera_scale: Vec2,
     pub debug_mode: bool,
+    pub debug_layers: HashSet<DebugLayer>,
+    pub show_fps: bool,
     pub player_alive: bool,
+    pub can_continue: bool,
+    pub mouse_control: bool,
+    /// Off while the console takes the keyboard, so Enter doesn't press buttons
+    pub ui_input: bool,
     pub stage: usize,
     pub stage_timer: f32,
     pub play_button: ButtonElement,
     pub quit_button: ButtonElement,
+    pub continue_button: ButtonElement,
+    pub settings_button: ButtonElement,
+    pub achievements_button: ButtonElement,
+    pub paused_element: TextElement,
+    pub resume_button: ButtonElement,
+    pub pause_settings_button: ButtonElement,
+    pub menu_button: ButtonElement,
+    pub back_button: ButtonElement,
+    pub master_volume_slider: SliderElement,
+    pub music_volume_slider: SliderElement,
+    pub sfx_volume_slider: SliderElement,
+    pub mute_button: ButtonElement,
+    pub fullscreen_button: ButtonElement,
+    pub head_control_button: ButtonElement,
+    pub show_fps_button: ButtonElement,
+    pub difficulty_button: ButtonElement,
+    pub dynamic_difficulty_button: ButtonElement,
     pub tutorial_texture: TextureElement,
     pub fps_element: FPSElement,
     pub stage_element: TextElement,
+    pub mutator_hint_element: TextElement,
+    pub mutators_element: TextElement,
+    /// Shown on the tutorial screen before the run has its own mutators
+    pub mutator_picks: String,
     pub death_element: TextElement,
     pub reset_element: TextElement,
+    pub export_element: TextElement,
+    pub summary_elements: Vec<TextElement>,
+    pub achievement_elements: Vec<TextElement>,
+    pub toast_element: TextElement,
+    pub minimap: MinimapElement,
+    pub toasts: VecDeque<String>,
+    pub toast_timer: f32,
 }
 
 impl UIState {
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.show_fps = settings.show_fps;
+        self.master_volume_slider.value = settings.master_volume;
+        self.music_volume_slider.value = settings.music_volume;
+        self.sfx_volume_slider.value = settings.sfx_volume;
+        self.mute_button.text_element.text = format!("MUTE: {}", on_off(settings.mute));
+        self.fullscreen_button.text_element.text =
+            format!("FULLSCREEN: {}", on_off(settings.fullscreen));
+        self.head_control_button.text_element.text = format!(
+            "HEAD: {}",
+            match settings.head_control_mode {
+                HeadControlMode::Mouse => "MOUSE",
+                HeadControlMode::Keys => "KEYS",
+            }
+        );
+        self.show_fps_button.text_element.text = format!("FPS: {}", on_off(settings.show_fps));
+        self.difficulty_button.text_element.text = settings.difficulty.name().to_owned();
+        self.dynamic_difficulty_button.text_element.text =
+            format!("DYNAMIC: {}", on_off(settings.dynamic_difficulty));
+    }
+
+    pub fn set_summary(&mut self, stats: &RunStats) {
+        let mut lines = vec![
+            format!("DIFFICULTY: {}", stats.difficulty.name()),
+            format!("KILLS: {}", stats.total_kills()),
+        ];
+        lines.extend(
+            stats
+                .kills
+                .iter()
+                .map(|(kind, kills)| format!("{}: {}", kind.to_uppercase(), kills)),
+        );
+        lines.push(format!(
+            "HEAD HITS: {}   BODY HITS: {}",
+            stats.head_hits, stats.body_hits
+        ));
+        lines.push(format!("STRONGEST HIT: {:.0}", stats.strongest_hit));
+        lines.push(format!("DAMAGE TAKEN: {:.0}", stats.total_damage_taken()));
+        lines.extend(
+            stats
+                .damage_taken
+                .iter()
+                .map(|(source, damage)| format!("{}: {:.0}", source.to_uppercase(), damage)),
+        );
+        lines.push(format!("HEALING: {:.0}", stats.healing_received));
+        if let Some(wave_time) = stats.average_wave_time() {
+            lines.push(format!("AVERAGE WAVE TIME: {:.1}s", wave_time));
+        }
+        lines.push(format!("DISTANCE: {:.0}", stats.distance_traveled));
+        if !stats.mutators.is_empty() {
+            lines.push(format!("MUTATORS: {}", stats.mutators.join(", ")));
+        }
+
+        self.summary_elements = lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| {
+                TextElement::new(
+                    line,
+                    SUMMARY_FONT_SIZE,
+                    WHITE,
+                    UIObject::new(
+                        vec2(0.5, 0.5),
+                        vec2(0.0, SUMMARY_TOP + index as f32 * SUMMARY_FONT_SIZE * 0.75),
+                        UIScaleMode::KeepRatio,
+                    ),
+                )
+            })
+            .collect();
+    }
+
+    pub fn set_achievements(&mut self, achievements: &Achievements) {
+        self.achievement_elements = AchievementId::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, &achievement)| {
+                let unlocked = achievements.is_unlocked(achievement);
+                TextElement::new(
+                    format!(
+                        "[{}] {} - {}",
+                        if unlocked { "X" } else { " " },
+                        achievement.name(),
+                        achievement.description()
+                    ),
+                    ACHIEVEMENTS_FONT_SIZE,
+                    if unlocked { WHITE } else { GRAY },
+                    UIObject::new(
+                        vec2(0.5, 1.0),
+                        vec2(
+                            0.0,
+                            ACHIEVEMENTS_TOP + index as f32 * ACHIEVEMENTS_FONT_SIZE * 1.1,
+                        ),
+                        UIScaleMode::KeepRatio,
+                    ),
+                )
+            })
+            .collect();
+    }
+
+    fn pressed(&self) -> bool {
+        self.ui_input
+            && (is_mouse_button_pressed(MouseButton::Left) || is_key_pressed(KeyCode::Enter))
+    }
+
     pub fn update(
         &mut self,
         camera_scale: Vec2,
@@ -25,6 +169,11 @@ impl UIState {
         position: Vec2,
     ) -> Option<GameUpdate> {
         self.camera_scale = camera_scale;
+        let position = if self.mouse_control {
+            mouse_position().into()
+        } else {
+            position
+        };
         self.ui_scale = vec2(
             screen_width() / DEFAULT_WIDTH,
             screen_height() / DEFAULT_HEIGHT,
@@ -34,28 +183,153 @@ impl UIState {
             self.stage_timer -= delta_time;
         }
 
+        if self.toast_timer > 0.0 {
+            self.toast_timer -= delta_time;
+        } else if let Some(toast) = self.toasts.pop_front() {
+            self.toast_element.text = toast;
+            self.toast_timer = TOAST_SHOW_TIME;
+        }
+
         if is_key_pressed(KeyCode::F6) {
             self.debug_mode = !self.debug_mode;
         }
+        if self.debug_mode {
+            for &(layer, key) in &DebugLayer::ALL {
+                if is_key_pressed(key) && !self.debug_layers.remove(&layer) {
+                    self.debug_layers.insert(layer);
+                }
+            }
+        }
 
         let mut game_update = None;
         match self.state {
             GameState::Menu => {
-                let press =
-                    is_mouse_button_pressed(MouseButton::Left) || is_key_pressed(KeyCode::Enter);
+                let press = self.pressed();
                 self.play_button.hovering =
                     self.play_button
                         .point_inside(position, self.ui_scale, self.camera_scale);
                 self.play_button.update(delta_time);
+                self.continue_button.hovering = self.can_continue
+                    && self.continue_button.point_inside(
+                        position,
+                        self.ui_scale,
+                        self.camera_scale,
+                    );
+                self.continue_button.update(delta_time);
                 self.quit_button.hovering =
                     self.quit_button
                         .point_inside(position, self.ui_scale, self.camera_scale);
                 self.quit_button.update(delta_time);
+                self.settings_button.hovering =
+                    self.settings_button
+                        .point_inside(position, self.ui_scale, self.camera_scale);
+                self.settings_button.update(delta_time);
+                self.achievements_button.hovering = self.achievements_button.point_inside(
+                    position,
+                    self.ui_scale,
+                    self.camera_scale,
+                );
+                self.achievements_button.update(delta_time);
                 if press {
                     if self.play_button.hovering {
                         game_update = Some(GameUpdate::Start);
+                    } else if self.continue_button.hovering {
+                        game_update = Some(GameUpdate::Continue);
                     } else if self.quit_button.hovering {
                         game_update = Some(GameUpdate::Quit);
+                    } else if self.settings_button.hovering {
+                        game_update = Some(GameUpdate::OpenSettings);
+                    } else if self.achievements_button.hovering {
+                        game_update = Some(GameUpdate::OpenAchievements);
+                    }
+                }
+            }
+            GameState::Paused => {
+                let press = self.pressed();
+                let (ui_scale, camera_scale) = (self.ui_scale, self.camera_scale);
+                let buttons = [
+                    (&mut self.resume_button, GameUpdate::Resume),
+                    (&mut self.pause_settings_button, GameUpdate::OpenSettings),
+                    (&mut self.menu_button, GameUpdate::QuitToMenu),
+                ];
+                for (button, update) in buttons {
+                    button.hovering = button.point_inside(position, ui_scale, camera_scale);
+                    button.update(delta_time);
+                    if press && button.hovering {
+                        game_update = Some(update);
+                    }
+                }
+            }
+            GameState::Achievements => {
+                let press = self.pressed();
+                self.back_button.hovering =
+                    self.back_button
+                        .point_inside(position, self.ui_scale, self.camera_scale);
+                self.back_button.update(delta_time);
+                if press && self.back_button.hovering {
+                    game_update = Some(GameUpdate::BackToMenu);
+                }
+            }
+            GameState::Settings => {
+                let press = self.pressed();
+                let scroll = if self.ui_input { mouse_wheel().1 } else { 0.0 };
+                let (ui_scale, camera_scale) = (self.ui_scale, self.camera_scale);
+
+                let buttons = [
+                    (&mut self.back_button, GameUpdate::BackToMenu),
+                    (
+                        &mut self.mute_button,
+                        GameUpdate::ChangeSetting(SettingsChange::Mute),
+                    ),
+                    (
+                        &mut self.fullscreen_button,
+                        GameUpdate::ChangeSetting(SettingsChange::Fullscreen),
+                    ),
+                    (
+                        &mut self.head_control_button,
+                        GameUpdate::ChangeSetting(SettingsChange::HeadControlMode),
+                    ),
+                    (
+                        &mut self.show_fps_button,
+                        GameUpdate::ChangeSetting(SettingsChange::ShowFps),
+                    ),
+                    (
+                        &mut self.difficulty_button,
+                        GameUpdate::ChangeSetting(SettingsChange::Difficulty),
+                    ),
+                    (
+                        &mut self.dynamic_difficulty_button,
+                        GameUpdate::ChangeSetting(SettingsChange::DynamicDifficulty),
+                    ),
+                ];
+                for (button, update) in buttons {
+                    button.hovering = button.point_inside(position, ui_scale, camera_scale);
+                    button.update(delta_time);
+                    if press && button.hovering {
+                        game_update = Some(update);
+                    }
+                }
+
+                let sliders = [
+                    (
+                        &mut self.master_volume_slider,
+                        SettingsChange::MasterVolume as fn(f32) -> SettingsChange,
+                    ),
+                    (&mut self.music_volume_slider, SettingsChange::MusicVolume),
+                    (&mut self.sfx_volume_slider, SettingsChange::SfxVolume),
+                ];
+                for (slider, change) in sliders {
+                    slider.button.hovering = slider.point_inside(position, ui_scale, camera_scale);
+                    slider.update(delta_time);
+                    if slider.button.hovering {
+                        if press {
+                            game_update =
+                                Some(GameUpdate::ChangeSetting(change(slider.next_value())));
+                        } else if scroll != 0.0 {
+                            game_update = Some(GameUpdate::ChangeSetting(change(
+                                slider.scroll_value(scroll),
+                            )));
+                        }
                     }
                 }
             }
@@ -71,28 +345,90 @@ impl UIState {
         match self.state {
             GameState::Menu => {
                 self.play_button.draw(self.ui_scale, self.camera_scale);
+                if self.can_continue {
+                    self.continue_button.draw(self.ui_scale, self.camera_scale);
+                }
                 self.quit_button.draw(self.ui_scale, self.camera_scale);
+                self.settings_button.draw(self.ui_scale, self.camera_scale);
+                self.achievements_button
+                    .draw(self.ui_scale, self.camera_scale);
+            }
+            GameState::Paused => {
+                self.paused_element.draw(self.ui_scale, self.camera_scale);
+                self.resume_button.draw(self.ui_scale, self.camera_scale);
+                self.pause_settings_button
+                    .draw(self.ui_scale, self.camera_scale);
+                self.menu_button.draw(self.ui_scale, self.camera_scale);
+            }
+            GameState::Achievements => {
+                self.back_button.draw(self.ui_scale, self.camera_scale);
+                for achievement_element in &self.achievement_elements {
+                    achievement_element.draw(self.ui_scale, self.camera_scale);
+                }
+            }
+            GameState::Settings => {
+                self.back_button.draw(self.ui_scale, self.camera_scale);
+                self.master_volume_slider
+                    .draw(self.ui_scale, self.camera_scale);
+                self.music_volume_slider
+                    .draw(self.ui_scale, self.camera_scale);
+                self.sfx_volume_slider
+                    .draw(self.ui_scale, self.camera_scale);
+                self.mute_button.draw(self.ui_scale, self.camera_scale);
+                self.fullscreen_button
+                    .draw(self.ui_scale, self.camera_scale);
+                self.head_control_button
+                    .draw(self.ui_scale, self.camera_scale);
+                self.show_fps_button.draw(self.ui_scale, self.camera_scale);
+                self.difficulty_button
+                    .draw(self.ui_scale, self.camera_scale);
+                self.dynamic_difficulty_button
+                    .draw(self.ui_scale, self.camera_scale);
             }
             GameState::Pregame => {
                 self.tutorial_texture.draw(self.ui_scale, self.camera_scale);
+                self.mutator_hint_element
+                    .draw(self.ui_scale, self.camera_scale);
+                self.mutators_element.draw(self.ui_scale, self.camera_scale);
+            }
+            GameState::Game => {
+                if self.player_alive {
+                    self.minimap.draw(self.ui_scale, self.camera_scale);
+                    self.mutators_element.draw(self.ui_scale, self.camera_scale);
+                }
             }
-            _ => (),
         }
 
-        if self.debug_mode {
+        if self.toast_timer > 0.0 {
+            self.toast_element.draw(self.ui_scale, self.camera_scale);
+        }
+
+        if self.debug_mode || self.show_fps {
             self.fps_element.draw(self.ui_scale, self.camera_scale);
         }
 
         if !self.player_alive {
             self.stage_element.draw(self.ui_scale, self.camera_scale);
             self.death_element.draw(self.ui_scale, self.camera_scale);
+            for summary_element in &self.summary_elements {
+                summary_element.draw(self.ui_scale, self.camera_scale);
+            }
             self.reset_element.draw(self.ui_scale, self.camera_scale);
+            self.export_element.draw(self.ui_scale, self.camera_scale);
         } else if self.stage_timer > 0.0 {
             self.stage_element.draw(self.ui_scale, self.camera_scale);
         }
     }
 }
 
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "ON"
+    } else {
+        "OFF"
+    }
+}
+
 pub trait UIElement {
     fn update(&mut self, _delta_time: f32) {}
 
@@ -0,0 +1,104 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_PATH: &str = "settings.json";
+const MUSIC_VOLUME_SCALE: f32 = 0.05;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub mute: bool,
+    pub fullscreen: bool,
+    pub head_control_mode: HeadControlMode,
+    pub show_fps: bool,
+    /// Applied when a new run starts
+    pub difficulty: Difficulty,
+    pub dynamic_difficulty: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SettingsChange {
+    MasterVolume(f32),
+    MusicVolume(f32),
+    SfxVolume(f32),
+    Mute,
+    Fullscreen,
+    HeadControlMode,
+    ShowFps,
+    Difficulty,
+    DynamicDifficulty,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            mute: false,
+            fullscreen: false,
+            head_control_mode: HeadControlMode::Keys,
+            show_fps: false,
+            difficulty: Difficulty::Normal,
+            dynamic_difficulty: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        std::fs::read_to_string(SETTINGS_PATH)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(contents) => {
+                if let Err(error) = std::fs::write(SETTINGS_PATH, contents) {
+                    log_error!("Failed to save settings: {}", error);
+                }
+            }
+            Err(error) => log_error!("Failed to serialize settings: {}", error),
+        }
+    }
+
+    pub fn change(&mut self, change: SettingsChange) {
+        match change {
+            SettingsChange::MasterVolume(volume) => self.master_volume = volume.clamp(0.0, 1.0),
+            SettingsChange::MusicVolume(volume) => self.music_volume = volume.clamp(0.0, 1.0),
+            SettingsChange::SfxVolume(volume) => self.sfx_volume = volume.clamp(0.0, 1.0),
+            SettingsChange::Mute => self.mute = !self.mute,
+            SettingsChange::Fullscreen => self.fullscreen = !self.fullscreen,
+            SettingsChange::HeadControlMode => {
+                self.head_control_mode = match self.head_control_mode {
+                    HeadControlMode::Mouse => HeadControlMode::Keys,
+                    HeadControlMode::Keys => HeadControlMode::Mouse,
+                }
+            }
+            SettingsChange::ShowFps => self.show_fps = !self.show_fps,
+            SettingsChange::Difficulty => self.difficulty = self.difficulty.next(),
+            SettingsChange::DynamicDifficulty => self.dynamic_difficulty = !self.dynamic_difficulty,
+        }
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        if self.mute {
+            0.0
+        } else {
+            self.master_volume * self.music_volume * MUSIC_VOLUME_SCALE
+        }
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        if self.mute {
+            0.0
+        } else {
+            self.master_volume * self.sfx_volume
+        }
+    }
+}
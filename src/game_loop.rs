@@ -0,0 +1,55 @@
+/// Fixed timestep accumulator, turns variable frame times into whole simulation ticks
+pub struct GameLoop {
+    pub fixed_delta_time: f32,
+    /// Catch-up ticks allowed in one frame, the rest of a long frame is dropped
+    pub max_ticks: usize,
+    accumulator: f32,
+}
+
+impl GameLoop {
+    pub fn new(fixed_delta_time: f32, max_ticks: usize) -> Self {
+        Self {
+            fixed_delta_time,
+            max_ticks,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Adds the frame time and returns how many fixed ticks to run
+    pub fn advance(&mut self, delta_time: f32) -> usize {
+        self.accumulator += delta_time;
+        let mut ticks = 0;
+        while self.accumulator >= self.fixed_delta_time && ticks < self.max_ticks {
+            self.accumulator -= self.fixed_delta_time;
+            ticks += 1;
+        }
+        if self.accumulator >= self.fixed_delta_time {
+            log_debug!(
+                "dropped {:.0} fixed ticks",
+                (self.accumulator / self.fixed_delta_time).floor()
+            );
+            self.accumulator %= self.fixed_delta_time;
+        }
+        ticks
+    }
+
+    /// How far the current frame is between the last tick and the next one
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.fixed_delta_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caps_catch_up_ticks() {
+        let mut game_loop = GameLoop::new(0.25, 4);
+        assert_eq!(game_loop.advance(0.625), 2);
+        assert_eq!(game_loop.alpha(), 0.5);
+        assert_eq!(game_loop.advance(10.0), 4);
+        assert!(game_loop.alpha() < 1.0);
+        assert_eq!(game_loop.advance(0.0), 0);
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Set with the `BONKLE_LOG` environment variable, e.g. `BONKLE_LOG=debug`
+const LOG_ENV: &str = "BONKLE_LOG";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Off as u8);
+
+pub fn init() {
+    let level = match std::env::var(LOG_ENV).as_deref() {
+        Ok("error") => LogLevel::Error,
+        Ok("warn") => LogLevel::Warn,
+        Ok("info") => LogLevel::Info,
+        Ok("debug") => LogLevel::Debug,
+        _ => LogLevel::Off,
+    };
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(level: LogLevel) -> bool {
+    level != LogLevel::Off && level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::logging::enabled($level) {
+            eprintln!("[{:?}] {}", $level, format_args!($($arg)*));
+        }
+    };
+}
+
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        log!($crate::logging::LogLevel::Error, $($arg)*)
+    };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        log!($crate::logging::LogLevel::Warn, $($arg)*)
+    };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        log!($crate::logging::LogLevel::Info, $($arg)*)
+    };
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        log!($crate::logging::LogLevel::Debug, $($arg)*)
+    };
+}
@@ -1,39 +1,48 @@
 #![windows_subsystem = "windows"]
 use macroquad::prelude::*;
-use std::time::Instant;
 
+#[macro_use]
+mod logging;
 mod game;
+mod game_loop;
+mod profiler;
 
 use game::*;
+use game_loop::*;
 
 const FIXED_DELTA_TIME: f32 = 1.0 / 60.0;
+/// A quarter of a second worth of ticks
+const MAX_TICKS_PER_FRAME: usize = 15;
 
 #[macroquad::main("GMTK Game Jam 2021")]
 async fn main() {
+    logging::init();
     let mut game = Game::new().await;
-    let mut frame_time = 0.0;
+    let mut game_loop = GameLoop::new(FIXED_DELTA_TIME, MAX_TICKS_PER_FRAME);
+    prevent_quit();
     loop {
-        println!("---- next frame ----");
+        if is_quit_requested() {
+            game.quit();
+            break;
+        }
         let delta_time = get_frame_time();
-        frame_time += delta_time;
-        let time = Instant::now();
-        game.update(delta_time);
-        println!("update: {}ms", time.elapsed().as_millis());
-        let time = Instant::now();
-        let mut frames = 0;
-        while frame_time >= FIXED_DELTA_TIME {
-            game.fixed_update(FIXED_DELTA_TIME);
-            frame_time -= FIXED_DELTA_TIME;
+        profiler::measure("update", || game.update(delta_time));
+        // Sampled after the update so a pause takes effect on the same frame
+        let mut frames = game_loop.advance(delta_time * game.time_scale());
+        if game.take_step() {
             frames += 1;
         }
-        println!(
-            "fixed_update: {}ms / {} frames",
-            time.elapsed().as_millis(),
+        profiler::measure("fixed_update", || {
+            for _ in 0..frames {
+                game.fixed_update(FIXED_DELTA_TIME);
+            }
+        });
+        profiler::measure("draw", || game.draw(game_loop.alpha()));
+        log_debug!(
+            "frame: {:.2}ms, {} fixed updates",
+            delta_time * 1000.0,
             frames
         );
-        let time = Instant::now();
-        game.draw();
-        println!("draw: {}ms", time.elapsed().as_millis());
         next_frame().await;
     }
 }
@@ -0,0 +1,74 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// How many samples the rolling average and the graph cover
+pub const HISTORY_LENGTH: usize = 120;
+
+#[derive(Default)]
+pub struct Profiler {
+    pub scopes: Vec<ScopeStats>,
+}
+
+pub struct ScopeStats {
+    pub name: &'static str,
+    /// Durations in milliseconds, newest last
+    pub history: VecDeque<f32>,
+    sum: f32,
+}
+
+impl ScopeStats {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            history: VecDeque::with_capacity(HISTORY_LENGTH),
+            sum: 0.0,
+        }
+    }
+
+    fn record(&mut self, millis: f32) {
+        if self.history.len() == HISTORY_LENGTH {
+            self.sum -= self.history.pop_front().unwrap();
+        }
+        self.history.push_back(millis);
+        self.sum += millis;
+    }
+
+    pub fn average(&self) -> f32 {
+        if self.history.is_empty() {
+            0.0
+        } else {
+            self.sum / self.history.len() as f32
+        }
+    }
+}
+
+impl Profiler {
+    fn record(&mut self, name: &'static str, millis: f32) {
+        match self.scopes.iter_mut().find(|scope| scope.name == name) {
+            Some(scope) => scope.record(millis),
+            None => {
+                let mut scope = ScopeStats::new(name);
+                scope.record(millis);
+                self.scopes.push(scope);
+            }
+        }
+    }
+}
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::default());
+}
+
+/// Runs `f` and records how long it took under `name`
+pub fn measure<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    let millis = start.elapsed().as_secs_f32() * 1000.0;
+    PROFILER.with(|profiler| profiler.borrow_mut().record(name, millis));
+    result
+}
+
+pub fn with<T>(f: impl FnOnce(&Profiler) -> T) -> T {
+    PROFILER.with(|profiler| f(&profiler.borrow()))
+}